@@ -0,0 +1,30 @@
+/// Host hooks consulted when Luau looks up an alias
+///
+/// Alias targets returned by hooks must be absolute VFS paths; they are navigated to through
+/// [`AssetRequirer`](crate::AssetRequirer)'s `jump_to_alias`. Lookup precedence is:
+///
+/// 1. [`AliasHooks::override_alias`]
+/// 2. `.luaurc` files, nearest to the requiring module first
+/// 3. aliases registered with [`AssetRequirer::with_alias`](crate::AssetRequirer::with_alias)
+/// 4. [`AliasHooks::fallback_alias`]
+///
+/// Hooks are applied by rewriting configs as they are read, so once hooks are set any `.luaurc`
+/// that cannot be parsed fails to load rather than being served without them.
+pub trait AliasHooks {
+    /// Names of the aliases these hooks can provide, in lowercase
+    ///
+    /// Luau only looks up aliases that appear in a config, so these names are added to the root
+    /// config for [`AliasHooks::fallback_alias`] and [`AliasHooks::override_alias`] to answer.
+    /// Overrides also apply to any alias defined by a `.luaurc`, whether listed here or not.
+    fn names(&self) -> Vec<String>;
+
+    /// Returns a target for `alias` that takes precedence over every `.luaurc`
+    fn override_alias(&self, _alias: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns a target for `alias` when no `.luaurc` defines it
+    fn fallback_alias(&self, _alias: &str) -> Option<String> {
+        None
+    }
+}
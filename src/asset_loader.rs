@@ -0,0 +1,98 @@
+use mluau::prelude::*;
+
+/// Loads a non-Luau module (keyed by file extension) into a Lua value
+pub trait AssetLoader {
+    fn load(&self, lua: &Lua, path: &str, contents: Vec<u8>) -> LuaResult<LuaValue>;
+}
+
+impl<F> AssetLoader for F
+where
+    F: Fn(&Lua, &str, Vec<u8>) -> LuaResult<LuaValue>,
+{
+    fn load(&self, lua: &Lua, path: &str, contents: Vec<u8>) -> LuaResult<LuaValue> {
+        self(lua, path, contents)
+    }
+}
+
+/// Decodes `.json` files into Lua tables
+pub struct JsonLoader;
+
+impl AssetLoader for JsonLoader {
+    fn load(&self, lua: &Lua, path: &str, contents: Vec<u8>) -> LuaResult<LuaValue> {
+        let value: serde_json::Value = serde_json::from_slice(&contents)
+            .map_err(|e| LuaError::external(format!("Failed to decode {path}: {e}")))?;
+        json_to_lua(lua, &value)
+    }
+}
+
+fn json_to_lua(lua: &Lua, value: &serde_json::Value) -> LuaResult<LuaValue> {
+    Ok(match value {
+        serde_json::Value::Null => LuaValue::Nil,
+        serde_json::Value::Bool(b) => LuaValue::Boolean(*b),
+        serde_json::Value::Number(n) => LuaValue::Number(n.as_f64().unwrap_or(f64::NAN)),
+        serde_json::Value::String(s) => LuaValue::String(lua.create_string(s)?),
+        serde_json::Value::Array(arr) => {
+            let table = lua.create_table_with_capacity(arr.len(), 0)?;
+            for (i, v) in arr.iter().enumerate() {
+                table.raw_set(i + 1, json_to_lua(lua, v)?)?;
+            }
+            LuaValue::Table(table)
+        }
+        serde_json::Value::Object(obj) => {
+            let table = lua.create_table_with_capacity(0, obj.len())?;
+            for (k, v) in obj {
+                table.raw_set(k.as_str(), json_to_lua(lua, v)?)?;
+            }
+            LuaValue::Table(table)
+        }
+    })
+}
+
+/// Decodes `.toml` files into Lua tables
+#[cfg(feature = "toml")]
+pub struct TomlLoader;
+
+#[cfg(feature = "toml")]
+impl AssetLoader for TomlLoader {
+    fn load(&self, lua: &Lua, path: &str, contents: Vec<u8>) -> LuaResult<LuaValue> {
+        let text = String::from_utf8(contents)
+            .map_err(|e| LuaError::external(format!("Failed to decode {path}: {e}")))?;
+        let table: toml::Table = toml::from_str(&text)
+            .map_err(|e| LuaError::external(format!("Failed to decode {path}: {e}")))?;
+        toml_to_lua(lua, &toml::Value::Table(table))
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_to_lua(lua: &Lua, value: &toml::Value) -> LuaResult<LuaValue> {
+    Ok(match value {
+        toml::Value::String(s) => LuaValue::String(lua.create_string(s)?),
+        toml::Value::Integer(i) => LuaValue::Number(*i as f64),
+        toml::Value::Float(f) => LuaValue::Number(*f),
+        toml::Value::Boolean(b) => LuaValue::Boolean(*b),
+        toml::Value::Datetime(dt) => LuaValue::String(lua.create_string(dt.to_string())?),
+        toml::Value::Array(arr) => {
+            let table = lua.create_table_with_capacity(arr.len(), 0)?;
+            for (i, v) in arr.iter().enumerate() {
+                table.raw_set(i + 1, toml_to_lua(lua, v)?)?;
+            }
+            LuaValue::Table(table)
+        }
+        toml::Value::Table(tab) => {
+            let table = lua.create_table_with_capacity(0, tab.len())?;
+            for (k, v) in tab {
+                table.raw_set(k.as_str(), toml_to_lua(lua, v)?)?;
+            }
+            LuaValue::Table(table)
+        }
+    })
+}
+
+/// Returns the raw contents of a file as a Lua string
+pub struct TextLoader;
+
+impl AssetLoader for TextLoader {
+    fn load(&self, lua: &Lua, _path: &str, contents: Vec<u8>) -> LuaResult<LuaValue> {
+        Ok(LuaValue::String(lua.create_string(contents)?))
+    }
+}
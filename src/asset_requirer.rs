@@ -1,14 +1,43 @@
+use super::alias_hooks::AliasHooks;
+use super::asset_loader::AssetLoader;
+use super::bundler::{Bundle, build_bundle};
+use super::cache_key::{CacheKeyStrategy, sha256_hex};
+use super::config_luau::ConfigLuauCache;
+use super::events::{EventHandler, RequireEvent};
 use super::fswrapper::FilesystemWrapper;
-use super::utils::is_absolute_path;
-use super::vfs_navigator::{NavigationStatus, VfsNavigator};
+use super::limits::{DepthGuard, LimitError, ResourceLimits};
+use super::lockfile::Lockfile;
+use super::luaurc::{Entry, Node, parse_luaurc, write_luaurc};
+use super::luaurc_lint::{LuaurcSeverity, lint_luaurc_source};
+use super::module_cache::ModuleCache;
+use super::require_graph::{RequireGraph, build_require_graph};
+use super::resolver::{ResolveError, resolve_require};
+use super::source_transform::SourceTransform;
+use super::symlinks::{CanonicalPath, RootEscapeError, SymlinkEscapePolicy, SymlinkResolver};
+use super::utils::normalize_path;
+use super::vfs_navigator::{CaseSensitivity, NavigationStatus, VfsNavigator};
 use mluau::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
 use std::io::Result as IoResult;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Instant;
 
 pub trait IntoNavError {
     fn into_nav_error(self) -> Result<(), LuaNavigateError>;
 }
 
+#[cfg(feature = "tracing")]
+fn nav_status(result: &Result<(), LuaNavigateError>) -> &'static str {
+    match result {
+        Ok(()) => "success",
+        Err(LuaNavigateError::NotFound) => "not_found",
+        Err(LuaNavigateError::Ambiguous) => "ambiguous",
+        Err(LuaNavigateError::Other(_)) => "error",
+    }
+}
+
 impl IntoNavError for Result<NavigationStatus, crate::Error> {
     fn into_nav_error(self) -> Result<(), LuaNavigateError> {
         match self {
@@ -24,19 +53,722 @@ impl IntoNavError for Result<NavigationStatus, crate::Error> {
     }
 }
 
+#[derive(Clone)]
 pub struct AssetRequirer {
     cache_prefix: String,
     vfs: VfsNavigator,
     global_table: LuaTable,
+    asset_loaders: HashMap<String, Rc<dyn AssetLoader>>,
+    source_transforms: Vec<Rc<dyn SourceTransform>>,
+    aliases: BTreeMap<String, String>,
+    alias_hooks: Option<Rc<dyn AliasHooks>>,
+    strict_config: bool,
+    virtual_configs: HashMap<String, Vec<u8>>,
+    symlinks: Option<SymlinkResolver>,
+    root_jail: Option<SymlinkResolver>,
+    // Whether a config was looked for since the last navigation, as Luau does while it climbs
+    // towards the root searching for an alias
+    config_checked: Cell<bool>,
+    cache_key_strategy: CacheKeyStrategy,
+    module_cache: ModuleCache,
+    event_handler: Option<EventHandler>,
+    limits: ResourceLimits,
+    require_depth: Rc<Cell<usize>>,
+    modules_loaded: Rc<Cell<usize>>,
+    lockfile: Option<Rc<Lockfile>>,
+    verify_configs: bool,
+    config_luau_cache: ConfigLuauCache,
 }
 
 impl AssetRequirer {
+    /// Creates a requirer loading modules from `fs`
+    ///
+    /// The requirer only ever reads from `fs` (and from filesystems mounted with
+    /// [`with_alias_fs`](Self::with_alias_fs)), so it can be backed by a
+    /// [`FilesystemWrapper::read_only`] filesystem.
     pub fn new(fs: FilesystemWrapper, cache_prefix: String, global_table: LuaTable) -> Self {
         Self {
             cache_prefix,
             vfs: VfsNavigator::new(fs),
             global_table,
+            asset_loaders: HashMap::new(),
+            source_transforms: Vec::new(),
+            aliases: BTreeMap::new(),
+            alias_hooks: None,
+            strict_config: false,
+            virtual_configs: HashMap::new(),
+            symlinks: None,
+            root_jail: None,
+            config_checked: Cell::new(false),
+            cache_key_strategy: CacheKeyStrategy::default(),
+            module_cache: ModuleCache::default(),
+            event_handler: None,
+            limits: ResourceLimits::default(),
+            require_depth: Rc::new(Cell::new(0)),
+            modules_loaded: Rc::new(Cell::new(0)),
+            lockfile: None,
+            verify_configs: false,
+            config_luau_cache: ConfigLuauCache::default(),
+        }
+    }
+
+    /// Verifies every module against the hashes in `lockfile` before it is compiled
+    ///
+    /// Modules missing from the lockfile or whose contents do not match it fail to load with an
+    /// [`IntegrityError`](crate::IntegrityError). With `verify_configs`, `.luaurc` and
+    /// `.config.luau` files are verified the same way; virtual configs are never checked.
+    pub fn with_lockfile(mut self, lockfile: Lockfile, verify_configs: bool) -> Self {
+        self.lockfile = Some(Rc::new(lockfile));
+        self.verify_configs = verify_configs;
+        self
+    }
+
+    /// Sets limits on module and config sizes, require nesting and the number of modules loaded
+    ///
+    /// Exceeding a limit fails the require with a [`LimitError`].
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets a handler receiving [`RequireEvent`]s as modules are resolved, compiled and executed
+    pub fn with_event_handler(mut self, handler: impl Fn(&RequireEvent) + 'static) -> Self {
+        let handler: EventHandler = Rc::new(handler);
+        self.vfs.set_event_handler(handler.clone());
+        self.event_handler = Some(handler);
+        self
+    }
+
+    #[cfg(feature = "tracing")]
+    fn record_navigation(
+        &self,
+        span: &tracing::span::EnteredSpan,
+        result: &Result<(), LuaNavigateError>,
+    ) {
+        span.record("module_path", self.vfs.get_absolute_module_path());
+        span.record("status", nav_status(result));
+    }
+
+    fn emit(&self, event: impl FnOnce() -> RequireEvent) {
+        if let Some(handler) = self.event_handler.as_ref() {
+            handler(&event());
+        }
+    }
+
+    /// Returns a handle for inspecting and unloading the modules loaded by this requirer
+    pub fn module_cache(&self) -> ModuleCache {
+        self.module_cache.clone()
+    }
+
+    /// Sets how cache keys are computed for loaded modules
+    pub fn with_cache_key_strategy(mut self, strategy: CacheKeyStrategy) -> Self {
+        self.cache_key_strategy = strategy;
+        self
+    }
+
+    /// Canonicalises module paths through symlinks before computing cache keys
+    ///
+    /// `root` must be the host directory backing the filesystem (i.e. the root of the
+    /// `PhysicalFS`), so that a module reachable through several symlinked paths is only loaded
+    /// once. `policy` decides whether modules whose real path escapes `root` are loaded.
+    pub fn with_symlink_resolution(
+        mut self,
+        root: impl Into<PathBuf>,
+        policy: SymlinkEscapePolicy,
+    ) -> Self {
+        self.symlinks = Some(SymlinkResolver::new(root.into(), policy));
+        self
+    }
+
+    /// Confines every resolution to the host directory `root` backing the filesystem
+    ///
+    /// Navigating outside `root` through `..`, an absolute alias or a symlink fails with a
+    /// [`RootEscapeError`], regardless of the symlink escape policy. Mounted alias filesystems
+    /// are only checked for `..` components.
+    pub fn with_root_jail(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root_jail = Some(SymlinkResolver::new(
+            root.into(),
+            SymlinkEscapePolicy::Reject,
+        ));
+        self
+    }
+
+    /// Attaches a synthetic `.luaurc` with the given JSON contents to the directory `dir`
+    ///
+    /// The config is served as if a `.luaurc` file existed in that directory, shadowing any
+    /// `.luaurc` or `.config.luau` actually present there. This allows giving read-only sources,
+    /// such as embedded libraries, their own aliases.
+    pub fn with_virtual_config(mut self, dir: &str, contents: impl Into<Vec<u8>>) -> Self {
+        let dir = FilesystemWrapper::path_fix(dir.to_string());
+        let luaurc_path = format!("{}/.luaurc", dir.trim_end_matches('/'));
+        self.virtual_configs.insert(luaurc_path, contents.into());
+        self
+    }
+
+    /// Sets how module paths are matched against file names
+    pub fn with_case_sensitivity(mut self, case_sensitivity: CaseSensitivity) -> Self {
+        self.vfs.set_case_sensitivity(case_sensitivity);
+        self
+    }
+
+    /// Sets a handler called with the requested and actual paths whenever
+    /// [`CaseSensitivity::WarnOnMismatch`] finds a differently-cased match
+    pub fn with_case_mismatch_handler(mut self, handler: impl Fn(&str, &str) + 'static) -> Self {
+        self.vfs.set_case_mismatch_handler(Rc::new(handler));
+        self
+    }
+
+    /// Sets whether `.luaurc` files are linted before use, failing on any error-level diagnostic
+    ///
+    /// See [`lint_luaurc`](crate::lint_luaurc) for the checks performed.
+    pub fn with_strict_config(mut self, strict: bool) -> Self {
+        self.strict_config = strict;
+        self
+    }
+
+    /// Registers `@name` as an alias for the directory `path` in this requirer's filesystem
+    ///
+    /// Registered aliases behave as if they were defined in a `.luaurc` at the root of the
+    /// filesystem: any `.luaurc` defining an alias of the same name (case-insensitively) takes
+    /// precedence over them.
+    pub fn with_alias(mut self, name: &str, path: &str) -> Self {
+        self.aliases.insert(
+            name.to_lowercase(),
+            FilesystemWrapper::path_fix(path.to_string()),
+        );
+        self
+    }
+
+    /// Registers `@name` as an alias for the root of a separate filesystem
+    ///
+    /// The filesystem is mounted at `/@name`, so modules inside it can require each other with
+    /// relative paths as usual. Precedence relative to `.luaurc` aliases is the same as for
+    /// [`AssetRequirer::with_alias`].
+    pub fn with_alias_fs(mut self, name: &str, fs: FilesystemWrapper) -> Self {
+        let name = name.to_lowercase();
+        let mount_point = format!("/@{name}");
+        self.vfs.add_mount(mount_point.clone(), fs);
+        self.aliases.insert(name, mount_point);
+        self
+    }
+
+    /// Sets the hooks used to override or provide fallbacks for aliases
+    ///
+    /// See [`AliasHooks`] for the resulting alias precedence.
+    pub fn with_alias_hooks(mut self, hooks: impl AliasHooks + 'static) -> Self {
+        self.alias_hooks = Some(Rc::new(hooks));
+        self
+    }
+
+    /// Adds a transform applied to Luau module source before compilation
+    ///
+    /// Transforms run in the order they are added, each receiving the output of the previous one
+    pub fn with_source_transform(mut self, transform: impl SourceTransform + 'static) -> Self {
+        self.source_transforms.push(Rc::new(transform));
+        self
+    }
+
+    /// Registers a loader for modules with the given file extension (without the leading dot)
+    ///
+    /// Such modules must be required with their extension, e.g. `require("./config.json")`,
+    /// unless extension probing is enabled with [`AssetRequirer::with_asset_extension_probing`]
+    pub fn with_asset_loader(
+        mut self,
+        extension: &str,
+        loader: impl AssetLoader + 'static,
+    ) -> Self {
+        self.vfs.add_asset_extension(extension);
+        self.asset_loaders
+            .insert(extension.to_string(), Rc::new(loader));
+        self
+    }
+
+    /// Sets whether extensionless requires also probe the extensions of registered asset loaders
+    pub fn with_asset_extension_probing(mut self, enabled: bool) -> Self {
+        self.vfs.set_probe_asset_extensions(enabled);
+        self
+    }
+
+    /// Resolves `path` as if it were required from `chunk_name`, without loading the module
+    ///
+    /// Returns the absolute VFS path of the module
+    pub fn resolve(&mut self, chunk_name: &str, path: &str) -> Result<String, ResolveError> {
+        resolve_require(&mut self.detached(), chunk_name, path)
+    }
+
+    /// Builds the static require graph of every Luau module in the filesystem, resolving
+    /// string-literal requires with this requirer's aliases and config
+    pub fn require_graph(&self) -> Result<RequireGraph, crate::Error> {
+        build_require_graph(self)
+    }
+
+    /// Bundles the module at `entry` and every module it statically requires
+    pub fn bundle(&self, entry: &str) -> Result<Bundle, crate::Error> {
+        build_bundle(self, entry)
+    }
+
+    /// Creates a `resolve(path)` Lua function sharing this requirer's filesystem and config
+    ///
+    /// The function returns the absolute VFS path of the module `path` would load when required
+    /// from the calling chunk, or `nil` plus the list of probed paths if it cannot be resolved
+    pub fn create_resolve_function(&self, lua: &Lua) -> LuaResult<LuaFunction> {
+        let requirer = RefCell::new(self.detached());
+        lua.create_function(move |lua, path: String| {
+            let chunk_name = lua
+                .inspect_stack(1, |debug| {
+                    debug.source().source.map(|source| source.into_owned())
+                })
+                .flatten()
+                .ok_or_else(|| LuaError::runtime("resolve must be called from a Luau chunk"))?;
+
+            match resolve_require(&mut requirer.borrow_mut(), &chunk_name, &path) {
+                Ok(resolved) => Ok((Some(resolved), None)),
+                Err(ResolveError::NotFound { probes } | ResolveError::Ambiguous { probes }) => {
+                    Ok((None, Some(probes)))
+                }
+                Err(ResolveError::UnknownAlias(_)) => Ok((None, Some(Vec::new()))),
+                Err(e) => Err(LuaError::external(e)),
+            }
+        })
+    }
+
+    /// Canonicalises the current module path if symlink resolution is enabled
+    ///
+    /// Returns `None` when symlink resolution is disabled or does not apply to the path.
+    fn canonical_path(&self) -> Option<std::io::Result<CanonicalPath>> {
+        let symlinks = self.symlinks.as_ref()?;
+        let path = self.vfs.get_absolute_file_path();
+        if self.vfs.is_mounted(path.to_string()) {
+            return None;
+        }
+
+        Some(symlinks.canonicalize(path))
+    }
+
+    /// Whether the current module escapes the root through a symlink and must be rejected
+    fn is_rejected_symlink(&self) -> bool {
+        let Some(symlinks) = self.symlinks.as_ref() else {
+            return false;
+        };
+
+        symlinks.policy == SymlinkEscapePolicy::Reject
+            && matches!(self.canonical_path(), Some(Ok(CanonicalPath::Outside(_))))
+    }
+
+    /// Checks that the current position lies inside the root jail, if one is configured
+    fn check_root_jail(&self) -> Result<(), RootEscapeError> {
+        let Some(jail) = self.root_jail.as_ref() else {
+            return Ok(());
+        };
+
+        let module_paths = [
+            self.vfs.get_module_path(),
+            self.vfs.get_absolute_module_path(),
+        ];
+        if let Some(path) = module_paths
+            .into_iter()
+            .find(|p| p.split('/').any(|c| c == ".."))
+        {
+            return Err(RootEscapeError {
+                path: path.to_string(),
+            });
+        }
+
+        // Paths that do not exist cannot be read, so only existing ones need canonicalising
+        let real_paths = [self.vfs.get_file_path(), self.vfs.get_absolute_file_path()];
+        for path in real_paths {
+            if !self.vfs.is_mounted(path.to_string())
+                && let Ok(CanonicalPath::Outside(_)) = jail.canonicalize(path)
+            {
+                return Err(RootEscapeError {
+                    path: path.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Under a root jail, rejects a path that climbs above the root, which the navigator would
+    /// otherwise clamp to the root
+    fn reject_escape(&self, path: &str) -> Result<(), LuaNavigateError> {
+        if self.root_jail.is_some() && normalize_path(Path::new(path)).starts_with("..") {
+            return Err(LuaNavigateError::Other(LuaError::external(
+                RootEscapeError {
+                    path: path.to_string(),
+                },
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks a successful navigation step against the root jail
+    fn confine(&self, result: Result<(), LuaNavigateError>) -> Result<(), LuaNavigateError> {
+        result?;
+        self.check_root_jail()
+            .map_err(|e| LuaNavigateError::Other(LuaError::external(e)))
+    }
+
+    /// Reads and compiles the module at `chunk_name`
+    fn load_module(&self, lua: &Lua, chunk_name: &str) -> LuaResult<LuaFunction> {
+        self.check_root_jail().map_err(LuaError::external)?;
+
+        if self.is_rejected_symlink() {
+            return Err(mluau::Error::external(format!(
+                "{chunk_name} resolves outside the filesystem root through a symlink"
+            )));
         }
+
+        self.check_module_limits(chunk_name)?;
+
+        let mut content = self
+            .vfs
+            .get_file(chunk_name.to_string())
+            .map_err(|e| mluau::Error::external(format!("Failed to fetch contents: {e:?}")))?;
+
+        if let Some(lockfile) = self.lockfile.as_ref() {
+            lockfile
+                .verify(chunk_name, &content)
+                .map_err(LuaError::external)?;
+        }
+
+        let asset_loader = Path::new(chunk_name)
+            .extension()
+            .and_then(|ext| self.asset_loaders.get(ext.to_string_lossy().as_ref()));
+        if let Some(asset_loader) = asset_loader {
+            let value = asset_loader.load(lua, chunk_name, content)?;
+            self.record_loaded(chunk_name);
+            return lua.create_function(move |_, _: LuaMultiValue| Ok(value.clone()));
+        }
+
+        for transform in self.source_transforms.iter() {
+            content = transform.transform(chunk_name, content).map_err(|e| {
+                mluau::Error::external(format!("Failed to transform {chunk_name}: {e}"))
+            })?;
+        }
+
+        let bytes = content.len();
+        let started = Instant::now();
+        let lv = lua
+            .load(content)
+            .set_mode(mluau::ChunkMode::Text)
+            .set_name(chunk_name)
+            .set_environment(self.global_table.clone())
+            .into_function()?;
+        self.record_loaded(chunk_name);
+        self.emit(|| RequireEvent::Compiled {
+            path: chunk_name.to_string(),
+            bytes,
+            duration: started.elapsed(),
+        });
+
+        let handler = self.event_handler.clone();
+        let depth = self
+            .limits
+            .max_require_depth
+            .map(|_| self.require_depth.clone());
+        if handler.is_none() && depth.is_none() {
+            return Ok(lv);
+        }
+
+        // Wrap the chunk to time its execution and track how deeply requires are nested
+        let path = chunk_name.to_string();
+        lua.create_function(move |_, args: LuaMultiValue| {
+            let _guard = depth.as_ref().map(DepthGuard::enter);
+            let started = Instant::now();
+            let result = lv.call::<LuaMultiValue>(args);
+            if let Some(handler) = handler.as_ref() {
+                match &result {
+                    Ok(_) => handler(&RequireEvent::Executed {
+                        path: path.clone(),
+                        duration: started.elapsed(),
+                    }),
+                    Err(e) => handler(&RequireEvent::ExecutionFailed {
+                        path: path.clone(),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+            result
+        })
+    }
+
+    /// Records a module that loaded successfully, counting it towards the module limit
+    fn record_loaded(&self, chunk_name: &str) {
+        self.module_cache
+            .record(self.cache_key(), chunk_name.to_string());
+        self.modules_loaded.set(self.modules_loaded.get() + 1);
+    }
+
+    /// Checks the module at `path` against the resource limits before it is read
+    fn check_module_limits(&self, path: &str) -> LuaResult<()> {
+        if let Some(limit) = self.limits.max_require_depth
+            && self.require_depth.get() >= limit
+        {
+            return Err(LuaError::external(LimitError::RequireTooDeep {
+                path: path.to_string(),
+                limit,
+            }));
+        }
+
+        if let Some(limit) = self.limits.max_modules
+            && self.modules_loaded.get() >= limit
+        {
+            return Err(LuaError::external(LimitError::TooManyModules {
+                path: path.to_string(),
+                limit,
+            }));
+        }
+
+        self.check_module_size(path)
+    }
+
+    /// Checks the size of the module at `path` against the resource limits before it is read
+    fn check_module_size(&self, path: &str) -> LuaResult<()> {
+        let Some(limit) = self.limits.max_module_size else {
+            return Ok(());
+        };
+
+        let size = self
+            .vfs
+            .file_size(path.to_string())
+            .map_err(|e| LuaError::external(format!("Failed to read metadata of {path}: {e}")))?;
+        if size > limit {
+            return Err(LuaError::external(LimitError::ModuleTooLarge {
+                path: path.to_string(),
+                size,
+                limit,
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the config at `path` against the resource limits before it is read
+    fn check_config_limits(&self, path: &str) -> IoResult<()> {
+        if let Some(limit) = self.limits.max_config_size {
+            let size = self
+                .vfs
+                .file_size(path.to_string())
+                .map_err(std::io::Error::other)?;
+            if size > limit {
+                return Err(std::io::Error::other(LimitError::ConfigTooLarge {
+                    path: path.to_string(),
+                    size,
+                    limit,
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the config at the current position in `.luaurc` (JSON) format
+    ///
+    /// `.config.luau` files are evaluated in a sandbox; having both kinds of config in the same
+    /// directory is an error.
+    fn read_config(&self) -> IoResult<Option<Vec<u8>>> {
+        let luaurc_path = self.vfs.get_luaurc_path();
+        if let Some(contents) = self.virtual_config(&luaurc_path) {
+            if self.strict_config {
+                self.check_luaurc(&luaurc_path, contents)?;
+            }
+            return Ok(Some(contents.to_vec()));
+        }
+
+        let config_luau_path = self.vfs.get_config_luau_path();
+        let has_luaurc = self.vfs.is_file(luaurc_path.clone()).unwrap_or(false);
+        let has_config_luau = self.vfs.is_file(config_luau_path.clone()).unwrap_or(false);
+
+        match (has_luaurc, has_config_luau) {
+            (true, true) => Err(std::io::Error::other(format!(
+                "Both {luaurc_path} and {config_luau_path} exist; only one config file is allowed per directory"
+            ))),
+            (true, false) => {
+                self.check_config_limits(&luaurc_path)?;
+                let contents = self
+                    .vfs
+                    .get_file(luaurc_path.clone())
+                    .map_err(std::io::Error::other)?;
+                self.verify_config(&luaurc_path, &contents)?;
+                if self.strict_config {
+                    self.check_luaurc(&luaurc_path, &contents)?;
+                }
+                Ok(Some(contents))
+            }
+            (false, true) => {
+                self.check_config_limits(&config_luau_path)?;
+                let source = self
+                    .vfs
+                    .get_file(config_luau_path.clone())
+                    .map_err(std::io::Error::other)?;
+                self.verify_config(&config_luau_path, &source)?;
+                self.config_luau_cache
+                    .evaluate(&config_luau_path, source)
+                    .map(Some)
+                    .map_err(std::io::Error::other)
+            }
+            (false, false) => Ok(None),
+        }
+    }
+
+    /// Checks a config file against the lockfile, if configs are verified
+    fn verify_config(&self, path: &str, contents: &[u8]) -> IoResult<()> {
+        match self.lockfile.as_ref() {
+            Some(lockfile) if self.verify_configs => lockfile
+                .verify(&FilesystemWrapper::path_fix(path.to_string()), contents)
+                .map_err(std::io::Error::other),
+            _ => Ok(()),
+        }
+    }
+
+    fn virtual_config(&self, luaurc_path: &str) -> Option<&[u8]> {
+        self.virtual_configs
+            .get(&FilesystemWrapper::path_fix(luaurc_path.to_string()))
+            .map(|c| c.as_slice())
+    }
+
+    /// Fails if linting the `.luaurc` at `path` reports any errors
+    fn check_luaurc(&self, path: &str, contents: &[u8]) -> IoResult<()> {
+        let exists = |p: &str| {
+            [p.to_string(), format!("{p}.luau"), format!("{p}.lua")]
+                .into_iter()
+                .any(|p| {
+                    self.vfs.is_file(p.clone()).unwrap_or(false)
+                        || self.vfs.is_dir(p).unwrap_or(false)
+                })
+        };
+
+        let errors = lint_luaurc_source(path, contents, &exists)
+            .into_iter()
+            .filter(|d| d.severity == LuaurcSeverity::Error)
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            errors.join("\n"),
+        ))
+    }
+
+    /// Whether `luaurc_path` is the root config and host aliases need injecting into it
+    fn has_root_aliases(&self, luaurc_path: &str) -> bool {
+        luaurc_path == "/.luaurc"
+            && (!self.aliases.is_empty()
+                || self
+                    .alias_hooks
+                    .as_ref()
+                    .is_some_and(|hooks| !hooks.names().is_empty()))
+    }
+
+    /// Applies alias overrides to a config and, for the root config, adds host aliases that the
+    /// config does not already define
+    ///
+    /// Fails if the config cannot be parsed, as serving it unpatched would silently drop host
+    /// aliases and overrides.
+    fn patch_config(&self, path: &str, contents: &[u8], is_root: bool) -> IoResult<Vec<u8>> {
+        let invalid = |reason: String| {
+            std::io::Error::other(format!("Cannot apply host aliases to {path}: {reason}"))
+        };
+
+        let mut root = match parse_luaurc(contents) {
+            Ok(Node::Object(root)) => root,
+            Ok(_) => return Err(invalid("config must be an object".to_string())),
+            Err(e) => return Err(invalid(e.to_string())),
+        };
+
+        if !root.iter().any(|e| e.key == "aliases") {
+            root.push(Entry::new("aliases", Node::Object(Vec::new())));
+        }
+
+        let mut tables = Vec::new();
+        for entry in root.iter_mut().filter(|e| e.key == "aliases") {
+            match &mut entry.value {
+                Node::Object(aliases) => tables.push(aliases),
+                _ => return Err(invalid("aliases must be an object".to_string())),
+            }
+        }
+
+        let is_defined = |tables: &[&mut Vec<Entry>], name: &str| {
+            tables
+                .iter()
+                .flat_map(|aliases| aliases.iter())
+                .any(|e| e.key.eq_ignore_ascii_case(name))
+        };
+
+        if is_root {
+            let mut added = Vec::new();
+            for (name, target) in self.aliases.iter() {
+                if !is_defined(&tables, name) {
+                    added.push(Entry::new(name, Node::String(target.clone())));
+                }
+            }
+
+            if let Some(hooks) = self.alias_hooks.as_ref() {
+                for name in hooks.names() {
+                    if is_defined(&tables, &name)
+                        || added.iter().any(|e| e.key.eq_ignore_ascii_case(&name))
+                    {
+                        continue;
+                    }
+
+                    if let Some(target) = hooks
+                        .fallback_alias(&name)
+                        .or_else(|| hooks.override_alias(&name))
+                    {
+                        let target = FilesystemWrapper::path_fix(target);
+                        added.push(Entry::new(&name, Node::String(target)));
+                    }
+                }
+            }
+
+            if let Some(aliases) = tables.last_mut() {
+                aliases.extend(added);
+            }
+        }
+
+        if let Some(hooks) = self.alias_hooks.as_ref() {
+            for alias in tables.iter_mut().flat_map(|aliases| aliases.iter_mut()) {
+                if let Some(target) = hooks.override_alias(&alias.key.to_lowercase()) {
+                    alias.value = Node::String(FilesystemWrapper::path_fix(target));
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        write_luaurc(&Node::Object(root), &mut out);
+        Ok(out)
+    }
+
+    /// Whether a root jail is configured and the navigator is at its root
+    pub(crate) fn is_at_jail_root(&self) -> bool {
+        self.root_jail.is_some() && self.vfs.get_absolute_module_path().is_empty()
+    }
+
+    /// Returns a copy for navigating outside of a require, which reports no events to the host's
+    /// event handler
+    pub(crate) fn detached(&self) -> Self {
+        let mut requirer = self.clone();
+        requirer.event_handler = None;
+        requirer.vfs.clear_event_handler();
+        requirer
+    }
+
+    pub(crate) fn navigator(&self) -> &VfsNavigator {
+        &self.vfs
+    }
+
+    pub(crate) fn probes(&self) -> &[String] {
+        self.vfs.get_probes()
+    }
+
+    pub(crate) fn absolute_path(&self) -> &str {
+        self.vfs.get_absolute_file_path()
     }
 }
 
@@ -46,84 +778,236 @@ impl LuaRequire for AssetRequirer {
     }
 
     fn reset(&mut self, chunk_name: &str) -> Result<(), LuaNavigateError> {
-        if chunk_name == "=repl" {
-            return self.vfs.reset_to_stdin().into_nav_error();
-        }
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "reset",
+            chunk = chunk_name,
+            module_path = tracing::field::Empty,
+            status = tracing::field::Empty,
+        )
+        .entered();
+
+        self.emit(|| RequireEvent::ResolveStarted {
+            chunk_name: chunk_name.to_string(),
+        });
 
-        self.vfs
-            .reset_to_path(&PathBuf::from(chunk_name))
-            .into_nav_error()
+        self.config_checked.set(false);
+        let result = if chunk_name == "=repl" {
+            self.vfs.reset_to_stdin().into_nav_error()
+        } else {
+            let result = self.reject_escape(chunk_name).and_then(|()| {
+                self.vfs
+                    .reset_to_path(&PathBuf::from(chunk_name))
+                    .into_nav_error()
+            });
+            self.confine(result)
+        };
+
+        #[cfg(feature = "tracing")]
+        self.record_navigation(&span, &result);
+        result
     }
 
     fn jump_to_alias(&mut self, path: &str) -> Result<(), LuaNavigateError> {
-        if !is_absolute_path(path) {
+        self.config_checked.set(false);
+        self.reject_escape(path)?;
+
+        // Alias targets are absolute VFS paths, which always start with `/` whatever the host
+        // platform
+        if !path.starts_with('/') {
             return Err(LuaNavigateError::NotFound);
         }
 
-        #[cfg(feature = "log")]
-        log::trace!("Reset to alias: {path}");
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "jump_to_alias",
+            path,
+            module_path = tracing::field::Empty,
+            status = tracing::field::Empty,
+        )
+        .entered();
 
-        self.vfs
+        let result = self
+            .vfs
             .reset_to_path(&PathBuf::from(path))
-            .into_nav_error()
+            .into_nav_error();
+        let result = self.confine(result);
+
+        #[cfg(feature = "tracing")]
+        self.record_navigation(&span, &result);
+        result
     }
 
     fn to_parent(&mut self) -> Result<(), LuaNavigateError> {
-        self.vfs.to_parent().into_nav_error()
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "to_parent",
+            module_path = tracing::field::Empty,
+            status = tracing::field::Empty,
+        )
+        .entered();
+
+        let result = if self.is_at_jail_root() {
+            // Climbing past the root after checking its config is an alias search that found no
+            // config defining the alias, which Luau reports when it gets `NotFound`
+            if self.config_checked.get() {
+                Err(LuaNavigateError::NotFound)
+            } else {
+                Err(LuaNavigateError::Other(LuaError::external(
+                    RootEscapeError {
+                        path: "/..".to_string(),
+                    },
+                )))
+            }
+        } else {
+            let result = self.vfs.to_parent().into_nav_error();
+            self.config_checked.set(false);
+            self.confine(result)
+        };
+
+        #[cfg(feature = "tracing")]
+        self.record_navigation(&span, &result);
+        result
     }
 
     fn to_child(&mut self, name: &str) -> Result<(), LuaNavigateError> {
-        self.vfs.to_child(name).into_nav_error()
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "to_child",
+            child = name,
+            module_path = tracing::field::Empty,
+            status = tracing::field::Empty,
+        )
+        .entered();
+
+        self.config_checked.set(false);
+        let result = self.vfs.to_child(name).into_nav_error();
+        let result = self.confine(result);
+
+        #[cfg(feature = "tracing")]
+        self.record_navigation(&span, &result);
+        result
     }
 
     fn has_module(&self) -> bool {
-        self.vfs
-            .fs
-            .is_file(self.vfs.get_file_path().to_string())
-            .unwrap_or(false)
+        let has_module = !self.is_rejected_symlink()
+            && self
+                .vfs
+                .is_file(self.vfs.get_file_path().to_string())
+                .unwrap_or(false);
+
+        self.emit(|| {
+            let path = self.vfs.get_absolute_file_path().to_string();
+            let probes = self.vfs.get_probes().to_vec();
+            if has_module {
+                RequireEvent::ResolveFinished { path, probes }
+            } else {
+                RequireEvent::ResolveFailed {
+                    module_path: path,
+                    ambiguous: false,
+                    probes,
+                }
+            }
+        });
+
+        has_module
     }
 
     fn cache_key(&self) -> String {
-        format!(
-            "{}@{}",
-            self.cache_prefix,
-            self.vfs.get_absolute_file_path()
-        )
+        let path = match self.canonical_path() {
+            Some(Ok(CanonicalPath::Inside(path))) => path,
+            Some(Ok(CanonicalPath::Outside(path))) => path.display().to_string(),
+            _ => self.vfs.get_absolute_file_path().to_string(),
+        };
+
+        match &self.cache_key_strategy {
+            CacheKeyStrategy::Path => format!("{}@{path}", self.cache_prefix),
+            CacheKeyStrategy::ContentHash => {
+                let file_path = self.vfs.get_absolute_file_path();
+                let contents = self
+                    .check_module_size(file_path)
+                    .ok()
+                    .and_then(|()| self.vfs.get_file(file_path.to_string()).ok());
+                match contents {
+                    Some(contents) => {
+                        format!("{}#sha256:{}", self.cache_prefix, sha256_hex(&contents))
+                    }
+                    // Let the loader report the limit or read error
+                    None => format!("{}@{path}", self.cache_prefix),
+                }
+            }
+            CacheKeyStrategy::PathAndVersion(version) => {
+                format!("{}@{path}@{version}", self.cache_prefix)
+            }
+            CacheKeyStrategy::Custom(f) => f(&self.cache_prefix, &path),
+        }
     }
 
     fn has_config(&self) -> bool {
-        self.vfs
-            .fs
-            .is_file(self.vfs.get_luaurc_path())
-            .unwrap_or(false)
+        self.config_checked.set(true);
+        let luaurc_path = self.vfs.get_luaurc_path();
+        if self.has_root_aliases(&luaurc_path) || self.virtual_config(&luaurc_path).is_some() {
+            return true;
+        }
+
+        self.vfs.is_file(luaurc_path).unwrap_or(false)
+            || self
+                .vfs
+                .is_file(self.vfs.get_config_luau_path())
+                .unwrap_or(false)
     }
 
     fn config(&self) -> IoResult<Vec<u8>> {
         let luaurc_path = self.vfs.get_luaurc_path();
 
-        #[cfg(feature = "log")]
-        log::trace!("Loading config from {luaurc_path:#?}");
-        self.vfs
-            .fs
-            .get_file(luaurc_path)
-            .map_err(std::io::Error::other)
+        #[cfg(feature = "tracing")]
+        tracing::trace!(path = %luaurc_path, "config");
+
+        let is_root = self.has_root_aliases(&luaurc_path);
+        let contents = match self.read_config()? {
+            Some(contents) => contents,
+            None if is_root => b"{}".to_vec(),
+            None => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No config found at {luaurc_path}"),
+                ));
+            }
+        };
+
+        if is_root || self.alias_hooks.is_some() {
+            return self.patch_config(&luaurc_path, &contents, is_root);
+        }
+
+        Ok(contents)
     }
 
     fn loader(&self, lua: &Lua) -> LuaResult<LuaFunction> {
         let chunk_name = self.vfs.get_absolute_file_path();
-        let content = self
-            .vfs
-            .fs
-            .get_file(chunk_name.to_string())
-            .map_err(|e| mluau::Error::external(format!("Failed to fetch contents: {e:?}")))?;
 
-        let lv = lua
-            .load(content)
-            .set_mode(mluau::ChunkMode::Text)
-            .set_name(chunk_name)
-            .set_environment(self.global_table.clone())
-            .into_function()?;
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "loader",
+            chunk = chunk_name,
+            module_path = self.vfs.get_absolute_module_path(),
+            status = tracing::field::Empty,
+        )
+        .entered();
+
+        self.emit(|| RequireEvent::LoadStarted {
+            path: chunk_name.to_string(),
+        });
+
+        let result = self.load_module(lua, chunk_name);
+        if let Err(e) = &result {
+            self.emit(|| RequireEvent::LoadFailed {
+                path: chunk_name.to_string(),
+                error: e.to_string(),
+            });
+        }
 
-        Ok(lv)
+        #[cfg(feature = "tracing")]
+        span.record("status", if result.is_ok() { "success" } else { "error" });
+        result
     }
 }
@@ -0,0 +1,236 @@
+// Bundling of an entry point and everything it statically requires into one artifact
+use super::asset_requirer::AssetRequirer;
+use super::fswrapper::FilesystemWrapper;
+use super::require_graph::{build_require_graph_from, is_luau_file};
+use mluau::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// The modules and configs reachable from an entry point
+///
+/// A bundle can be written out as a single Luau file with [`Bundle::to_luau`], or as a tree
+/// loadable with [`create_memory_vfs_from_map`](crate::create_memory_vfs_from_map) using
+/// [`Bundle::to_tree`] and [`Bundle::to_json`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bundle {
+    /// The absolute VFS path of the entry module
+    pub entry: String,
+    /// Contents of every bundled module and `.luaurc`, keyed by path relative to the root
+    ///
+    /// Configs are stored as served to Luau, so programmatic aliases, virtual configs and
+    /// `.config.luau` files are flattened into plain `.luaurc` files.
+    pub files: BTreeMap<String, String>,
+    /// The module each require path resolves to, keyed by the absolute path of the requiring
+    /// module
+    pub requires: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Bundle {
+    /// Returns the bundled files in the form accepted by
+    /// [`create_memory_vfs_from_map`](crate::create_memory_vfs_from_map)
+    pub fn to_tree(&self) -> HashMap<String, String> {
+        self.files
+            .iter()
+            .map(|(path, contents)| (path.clone(), contents.clone()))
+            .collect()
+    }
+
+    /// Serializes the bundle as JSON, to be read back with [`Bundle::tree_from_json`]
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "entry": self.entry,
+            "files": self.files,
+        }))
+        .expect("Bundle contents are always valid JSON")
+    }
+
+    /// Reads the file tree of a bundle serialized with [`Bundle::to_json`]
+    pub fn tree_from_json(bytes: &[u8]) -> Result<HashMap<String, String>, crate::Error> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)?;
+        let files = value
+            .get("files")
+            .and_then(|f| f.as_object())
+            .ok_or("bundle is missing its files table")?;
+
+        files
+            .iter()
+            .map(|(path, contents)| match contents.as_str() {
+                Some(contents) => Ok((path.clone(), contents.to_string())),
+                None => Err(format!("contents of {path} must be a string").into()),
+            })
+            .collect()
+    }
+
+    /// Renders the bundle as a single Luau chunk that runs the entry module
+    ///
+    /// Each module is inlined as a function receiving its own `require`, which only accepts the
+    /// string-literal paths found when bundling. Sources are embedded as stored, so modules
+    /// relying on source transforms or top-level `export type` declarations cannot be bundled
+    /// this way, nor can asset modules.
+    pub fn to_luau(&self) -> Result<String, crate::Error> {
+        let mut out = String::from("local __modules = {}\nlocal __requires = {}\n");
+        for (module, requires) in self.requires.iter() {
+            if !is_luau_file(module) {
+                return Err(format!("asset module {module} cannot be bundled into Luau").into());
+            }
+
+            let Some(source) = self.files.get(module.trim_start_matches('/')) else {
+                return Err(format!("source of {module} is missing from the bundle").into());
+            };
+            out.push_str(&format!(
+                "__modules[{}] = function(require, ...)\n{source}\nend\n",
+                quote(module)
+            ));
+
+            out.push_str(&format!("__requires[{}] = {{", quote(module)));
+            for (path, target) in requires.iter() {
+                if !self.requires.contains_key(target) {
+                    return Err(format!(
+                        "{module} requires {target}, which is missing from the bundle"
+                    )
+                    .into());
+                }
+                out.push_str(&format!("[{}] = {}, ", quote(path), quote(target)));
+            }
+            out.push_str("}\n");
+        }
+
+        out.push_str(&format!(
+            r#"
+local __loaded = {{}}
+local function __require(from)
+    return function(path)
+        local target = __requires[from][path]
+        if target == nil then
+            error(`{{path}} required from {{from}} was not bundled`, 2)
+        end
+        local loaded = __loaded[target]
+        if loaded == nil then
+            loaded = {{ value = __modules[target](__require(target)) }}
+            __loaded[target] = loaded
+        end
+        return loaded.value
+    end
+end
+
+return __modules[{entry}](__require({entry}), ...)
+"#,
+            entry = quote(&self.entry)
+        ));
+
+        Ok(out)
+    }
+}
+
+/// Quotes `s` as a Luau string literal
+fn quote(s: &str) -> String {
+    // Rust's escapes (`\n`, `\"`, `\u{..}`, ...) are all valid in Luau
+    format!("{s:?}")
+}
+
+/// Bundles the module at `entry` in `fs` and every module it statically requires
+pub fn bundle(fs: &FilesystemWrapper, entry: &str) -> Result<Bundle, crate::Error> {
+    let lua = Lua::new();
+    let requirer = AssetRequirer::new(fs.clone(), "bundle".to_string(), lua.globals());
+    build_bundle(&requirer, entry)
+}
+
+/// Bundles the module at `entry`, resolving requires with the requirer's aliases and config
+pub(crate) fn build_bundle(requirer: &AssetRequirer, entry: &str) -> Result<Bundle, crate::Error> {
+    let mut requirer = requirer.detached();
+    let entry = FilesystemWrapper::path_fix(entry.to_string());
+    if !requirer.navigator().is_file(entry.clone())? {
+        return Err(format!("entry point {entry} does not exist").into());
+    }
+
+    let graph = build_require_graph_from(&requirer, vec![entry.clone()])?;
+    let failures = graph
+        .unresolved()
+        .into_iter()
+        .chain(graph.ambiguous())
+        .map(|(module, r)| {
+            let error = r.resolved.as_ref().err().map(|e| e.to_string());
+            format!(
+                "{module}:{}:{}: {}",
+                r.line,
+                r.column,
+                error.unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        return Err(format!("unresolved requires:\n{}", failures.join("\n")).into());
+    }
+
+    let mut bundle = Bundle {
+        entry,
+        ..Default::default()
+    };
+    for (module, requires) in graph.modules.iter() {
+        let contents = requirer.navigator().get_file(module.clone())?;
+        let contents =
+            String::from_utf8(contents).map_err(|e| format!("{module} is not valid UTF-8: {e}"))?;
+        bundle
+            .files
+            .insert(module.trim_start_matches('/').to_string(), contents);
+        bundle.requires.insert(
+            module.clone(),
+            requires
+                .iter()
+                .filter_map(|r| Some((r.path.clone(), r.resolved.clone().ok()?)))
+                .collect(),
+        );
+
+        collect_configs(&mut requirer, module, &mut bundle.files)?;
+    }
+
+    // Asset modules are only reachable as require targets
+    for target in graph.modules.values().flatten() {
+        let Ok(target) = &target.resolved else {
+            continue;
+        };
+        if graph.modules.contains_key(target) {
+            continue;
+        }
+
+        let contents = requirer.navigator().get_file(target.clone())?;
+        let contents =
+            String::from_utf8(contents).map_err(|e| format!("{target} is not valid UTF-8: {e}"))?;
+        bundle
+            .files
+            .insert(target.trim_start_matches('/').to_string(), contents);
+        bundle.requires.insert(target.clone(), BTreeMap::new());
+        collect_configs(&mut requirer, target, &mut bundle.files)?;
+    }
+
+    Ok(bundle)
+}
+
+/// Adds every config in scope of `module` to `files`, as served to Luau
+fn collect_configs(
+    requirer: &mut AssetRequirer,
+    module: &str,
+    files: &mut BTreeMap<String, String>,
+) -> Result<(), crate::Error> {
+    if requirer.reset(module).is_err() {
+        return Err(format!("failed to navigate to {module}").into());
+    }
+
+    while requirer.to_parent().is_ok() {
+        if !requirer.has_config() {
+            continue;
+        }
+
+        let path = requirer.navigator().get_luaurc_path();
+        let path = path.trim_start_matches('/').to_string();
+        if files.contains_key(&path) {
+            continue;
+        }
+
+        let contents = requirer
+            .config()
+            .map_err(|e| format!("Failed to read config /{path}: {e}"))?;
+        files.insert(path, String::from_utf8(contents)?);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use sha2::{Digest, Sha256};
+use std::rc::Rc;
+
+/// How [`AssetRequirer`](crate::AssetRequirer) computes the key modules are cached under
+///
+/// Modules with equal keys share a single instance, so the strategy decides whether hosts share
+/// or isolate module instances.
+#[derive(Clone, Default)]
+pub enum CacheKeyStrategy {
+    /// `{cache_prefix}@{absolute_path}`
+    #[default]
+    Path,
+    /// `{cache_prefix}#sha256:{hash}`, deduplicating identical copies of a module
+    ContentHash,
+    /// `{cache_prefix}@{absolute_path}@{version}`
+    PathAndVersion(String),
+    /// A custom key computed from the cache prefix and absolute module path
+    Custom(Rc<dyn Fn(&str, &str) -> String>),
+}
+
+/// Returns the lowercase hex SHA-256 digest of `contents`
+pub(crate) fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -0,0 +1,130 @@
+// Support for Luau-syntax `.config.luau` files
+use super::cache_key::sha256_hex;
+use mluau::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Maximum number of interrupts a `.config.luau` may trigger before it is aborted
+const MAX_CONFIG_INTERRUPTS: u32 = 100_000;
+
+/// Maximum memory a `.config.luau` may allocate while it is evaluated
+const MAX_CONFIG_MEMORY: usize = 16 * 1024 * 1024;
+
+/// Maximum nesting depth of the tables a `.config.luau` may return
+const MAX_CONFIG_DEPTH: usize = 64;
+
+/// Evaluated configs or their errors, keyed by path and SHA-256 of the source
+type ConfigResults = HashMap<(String, String), Result<Vec<u8>, String>>;
+
+/// Results of evaluating `.config.luau` files, keyed by path and SHA-256 of the source
+///
+/// Luau asks for the config of every directory it walks through on each require, so evaluating
+/// unchanged files again would create a new Lua state every time.
+#[derive(Clone, Default)]
+pub(crate) struct ConfigLuauCache {
+    results: Rc<RefCell<ConfigResults>>,
+}
+
+impl ConfigLuauCache {
+    /// Evaluates the `.config.luau` at `path`, reusing the result for unchanged sources
+    pub fn evaluate(&self, path: &str, source: Vec<u8>) -> Result<Vec<u8>, crate::Error> {
+        let key = (path.to_string(), sha256_hex(&source));
+        if let Some(result) = self.results.borrow().get(&key) {
+            return result.clone().map_err(Into::into);
+        }
+
+        let result = evaluate_config_luau(path, source).map_err(|e| e.to_string());
+        self.results.borrow_mut().insert(key, result.clone());
+        result.map_err(Into::into)
+    }
+}
+
+/// Evaluates a `.config.luau` file in a fresh sandboxed Lua state, returning the `luau` table it
+/// produces serialized in `.luaurc` (JSON) format
+fn evaluate_config_luau(path: &str, source: Vec<u8>) -> Result<Vec<u8>, crate::Error> {
+    let lua = Lua::new();
+    lua.sandbox(true)?;
+    // Strings keep their metatable, so e.g. `("x"):rep(2^31)` must be stopped by the allocator
+    lua.set_memory_limit(MAX_CONFIG_MEMORY)?;
+
+    let interrupts = Cell::new(0u32);
+    lua.set_interrupt(move |_| {
+        interrupts.set(interrupts.get() + 1);
+        if interrupts.get() > MAX_CONFIG_INTERRUPTS {
+            return Err(LuaError::runtime("config took too long to evaluate"));
+        }
+        Ok(LuaVmState::Continue)
+    });
+
+    // Configs get no globals at all, only the ability to return a table
+    let env = lua.create_table()?;
+    let value: LuaValue = lua
+        .load(source)
+        .set_name(path)
+        .set_mode(mluau::ChunkMode::Text)
+        .set_environment(env)
+        .eval()?;
+
+    let LuaValue::Table(config) = value else {
+        return Err(format!("{path} must return a table").into());
+    };
+
+    let luau = match config.raw_get::<LuaValue>("luau")? {
+        LuaValue::Nil => serde_json::Value::Object(Default::default()),
+        LuaValue::Table(luau) => lua_to_json(LuaValue::Table(luau), &mut Vec::new())?,
+        _ => return Err(format!("{path}: the luau field must be a table").into()),
+    };
+
+    Ok(serde_json::to_vec(&luau)?)
+}
+
+/// Converts a config value to JSON, where `ancestors` holds the tables enclosing `value`
+fn lua_to_json(
+    value: LuaValue,
+    ancestors: &mut Vec<*const std::ffi::c_void>,
+) -> Result<serde_json::Value, crate::Error> {
+    Ok(match value {
+        LuaValue::Nil => serde_json::Value::Null,
+        LuaValue::Boolean(b) => serde_json::Value::Bool(b),
+        LuaValue::Integer(i) => serde_json::Value::from(i),
+        LuaValue::Number(n) => serde_json::Number::from_f64(n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        LuaValue::String(s) => serde_json::Value::String(s.to_str()?.to_string()),
+        LuaValue::Table(table) => {
+            let pointer = table.to_pointer();
+            if ancestors.contains(&pointer) {
+                return Err("config tables must not contain themselves".into());
+            }
+            if ancestors.len() >= MAX_CONFIG_DEPTH {
+                return Err(format!(
+                    "config tables are nested more than {MAX_CONFIG_DEPTH} levels deep"
+                )
+                .into());
+            }
+
+            ancestors.push(pointer);
+            let json = if table.raw_len() > 0 {
+                let mut arr = Vec::with_capacity(table.raw_len());
+                for value in table.sequence_values::<LuaValue>() {
+                    arr.push(lua_to_json(value?, ancestors)?);
+                }
+                serde_json::Value::Array(arr)
+            } else {
+                let mut obj = serde_json::Map::new();
+                for pair in table.pairs::<LuaValue, LuaValue>() {
+                    let (key, value) = pair?;
+                    let LuaValue::String(key) = key else {
+                        return Err("config table keys must be strings".into());
+                    };
+                    obj.insert(key.to_str()?.to_string(), lua_to_json(value, ancestors)?);
+                }
+                serde_json::Value::Object(obj)
+            };
+            ancestors.pop();
+            json
+        }
+        other => return Err(format!("unsupported config value: {}", other.type_name()).into()),
+    })
+}
@@ -0,0 +1,155 @@
+// Copying physical directory trees into memory
+use super::memoryvfs::MemoryTreeBuilder;
+use std::path::Path;
+
+/// Selects which files [`FilesystemWrapper::snapshot_from_dir`] copies
+///
+/// Globs are matched against paths relative to the snapshotted directory, using `/` separators.
+/// `*` matches within a path component, `**` matches across components and `?` matches a single
+/// character. With no include globs, every file not excluded is copied.
+///
+/// [`FilesystemWrapper::snapshot_from_dir`]: crate::FilesystemWrapper::snapshot_from_dir
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    max_file_size: Option<u64>,
+    max_total_size: Option<u64>,
+}
+
+impl SnapshotFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only copies files matching `glob` (or any other include glob)
+    pub fn with_include(mut self, glob: &str) -> Self {
+        self.include.push(glob.to_string());
+        self
+    }
+
+    /// Skips files and directories matching `glob`
+    pub fn with_exclude(mut self, glob: &str) -> Self {
+        self.exclude.push(glob.to_string());
+        self
+    }
+
+    /// Fails the snapshot if a selected file is larger than `bytes`
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Fails the snapshot if the selected files are larger than `bytes` in total
+    pub fn with_max_total_size(mut self, bytes: u64) -> Self {
+        self.max_total_size = Some(bytes);
+        self
+    }
+
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.iter().any(|glob| glob_matches(glob, path))
+    }
+
+    fn is_included(&self, path: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|glob| glob_matches(glob, path))
+    }
+}
+
+/// Copies the files under `root` selected by `filter` into a new tree
+///
+/// Symlinks to files are followed; symlinks to directories are skipped to avoid cycles.
+pub(crate) fn snapshot_dir(
+    root: &Path,
+    filter: &SnapshotFilter,
+) -> Result<MemoryTreeBuilder, crate::Error> {
+    let mut builder = MemoryTreeBuilder::new();
+    let mut total_size = 0u64;
+    let mut dirs = vec![String::new()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = std::fs::read_dir(root.join(&dir))?
+            .map(|entry| entry.map(|e| (e.file_name(), e.file_type())))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (name, file_type) in entries {
+            let name = name
+                .into_string()
+                .map_err(|name| format!("{name:?} in {} is not valid UTF-8", root.display()))?;
+            let path = if dir.is_empty() {
+                name
+            } else {
+                format!("{dir}/{name}")
+            };
+            if filter.is_excluded(&path) {
+                continue;
+            }
+
+            let file_type = file_type?;
+            if file_type.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+
+            let full_path = root.join(&path);
+            let metadata = std::fs::metadata(&full_path)?;
+            if !metadata.is_file() || !filter.is_included(&path) {
+                continue;
+            }
+
+            if let Some(max) = filter.max_file_size
+                && metadata.len() > max
+            {
+                return Err(format!(
+                    "{} is {} bytes, above the limit of {max} bytes",
+                    full_path.display(),
+                    metadata.len()
+                )
+                .into());
+            }
+
+            total_size += metadata.len();
+            if let Some(max) = filter.max_total_size
+                && total_size > max
+            {
+                return Err(format!(
+                    "snapshot of {} exceeds the total size limit of {max} bytes",
+                    root.display()
+                )
+                .into());
+            }
+
+            builder = builder.with_file(&path, std::fs::read(&full_path)?);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Matches `path` against a glob supporting `*`, `**` and `?`
+pub(crate) fn glob_matches(glob: &str, path: &str) -> bool {
+    fn match_bytes(glob: &[u8], path: &[u8]) -> bool {
+        match glob {
+            [] => path.is_empty(),
+            [b'*', b'*', b'/', rest @ ..] => {
+                // `**/` also matches no directories at all
+                match_bytes(rest, path)
+                    || path
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, b)| **b == b'/')
+                        .any(|(i, _)| match_bytes(rest, &path[i + 1..]))
+            }
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| match_bytes(rest, &path[i..])),
+            [b'*', rest @ ..] => {
+                let component_len = path.iter().position(|b| *b == b'/').unwrap_or(path.len());
+                (0..=component_len).any(|i| match_bytes(rest, &path[i..]))
+            }
+            [b'?', rest @ ..] => {
+                matches!(path.first(), Some(b) if *b != b'/') && match_bytes(rest, &path[1..])
+            }
+            [c, rest @ ..] => path.first() == Some(c) && match_bytes(rest, &path[1..]),
+        }
+    }
+
+    match_bytes(glob.as_bytes(), path.as_bytes())
+}
@@ -0,0 +1,33 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Instrumentation events emitted while modules are resolved and loaded
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequireEvent {
+    /// Resolution of a require from `chunk_name` started
+    ResolveStarted { chunk_name: String },
+    /// A require resolved to the module at `path`
+    ResolveFinished { path: String, probes: Vec<String> },
+    /// A navigation step or the final module lookup failed
+    ResolveFailed {
+        module_path: String,
+        ambiguous: bool,
+        probes: Vec<String>,
+    },
+    /// The module at `path` is not cached and is being loaded
+    LoadStarted { path: String },
+    /// The module source was compiled
+    Compiled {
+        path: String,
+        bytes: usize,
+        duration: Duration,
+    },
+    /// The module finished executing
+    Executed { path: String, duration: Duration },
+    /// The module could not be read, transformed or compiled
+    LoadFailed { path: String, error: String },
+    /// The module raised an error while executing
+    ExecutionFailed { path: String, error: String },
+}
+
+pub(crate) type EventHandler = Rc<dyn Fn(&RequireEvent)>;
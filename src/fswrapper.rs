@@ -1,3 +1,8 @@
+use super::dir_snapshot::{SnapshotFilter, snapshot_dir};
+use super::readonly::ReadOnlyFS;
+#[cfg(feature = "signed-manifest")]
+use super::signed_manifest::SignedManifestFS;
+use std::path::Path;
 use std::rc::Rc;
 use vfs::path::VfsFileType;
 use vfs::{FileSystem, VfsResult};
@@ -11,6 +16,37 @@ impl FilesystemWrapper {
         Self(Rc::new(fs))
     }
 
+    /// Copies the files under the physical directory `path` selected by `filter` into a
+    /// MemoryFS, isolating scripts from later changes on disk
+    pub fn snapshot_from_dir(
+        path: impl AsRef<Path>,
+        filter: &SnapshotFilter,
+    ) -> Result<Self, crate::Error> {
+        let tree = snapshot_dir(path.as_ref(), filter)?;
+        Ok(Self::new(tree.build()?))
+    }
+
+    /// Wraps `fs` so that every mutating operation fails
+    pub fn read_only<T: vfs::FileSystem>(fs: T) -> Self {
+        Self::new(ReadOnlyFS::new(fs))
+    }
+
+    /// Wraps `fs` so that only files listed in the signed `manifest` are visible, and only with
+    /// the contents it lists
+    ///
+    /// See [`SignedManifestFS`] for the manifest format.
+    #[cfg(feature = "signed-manifest")]
+    pub fn signed<T: vfs::FileSystem>(
+        fs: T,
+        manifest: &[u8],
+        signature: &[u8],
+        public_key: &[u8; 32],
+    ) -> Result<Self, crate::Error> {
+        Ok(Self::new(SignedManifestFS::new(
+            fs, manifest, signature, public_key,
+        )?))
+    }
+
     pub fn read_file(&self, path: &str) -> VfsResult<Vec<u8>> {
         self.read_to_bytes(path)
     }
@@ -29,11 +65,11 @@ impl FilesystemWrapper {
     pub fn is_file(&self, path: String) -> VfsResult<bool> {
         let path = Self::path_fix(path);
 
-        #[cfg(feature = "log")]
-        log::trace!("Checking if {path:#?} is a file");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%path, "is_file");
         if !self.exists(&path)? {
-            #[cfg(feature = "log")]
-            log::trace!("File {path:#?} does not exist");
+            #[cfg(feature = "tracing")]
+            tracing::trace!(%path, "is_file: not found");
             return Ok(false);
         }
 
@@ -51,21 +87,21 @@ impl FilesystemWrapper {
     pub fn is_dir(&self, path: String) -> VfsResult<bool> {
         let path = Self::path_fix(path);
 
-        #[cfg(feature = "log")]
-        log::trace!("Checking if {path:#?} is a directory");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%path, "is_dir");
         if path.is_empty() || path == "/" {
             return Ok(true);
         }
 
         if !self.exists(&path)? {
-            #[cfg(feature = "log")]
-            log::trace!("Directory {path:#?} does not exist");
+            #[cfg(feature = "tracing")]
+            tracing::trace!(%path, "is_dir: not found");
             return Ok(false);
         }
 
         let metadata = self.0.metadata(&path)?;
-        #[cfg(feature = "log")]
-        log::trace!("Metadata: {metadata:#?}");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%path, file_type = ?metadata.file_type, "is_dir: metadata");
         Ok(metadata.file_type == VfsFileType::Directory)
     }
 }
@@ -1,20 +1,67 @@
 // vendored from khronos
+mod alias_hooks;
+mod asset_loader;
 mod asset_requirer;
+mod bundler;
+mod cache_key;
+mod config_luau;
+mod dir_snapshot;
+mod events;
 mod fswrapper;
+mod limits;
+mod lockfile;
+mod luaurc;
+mod luaurc_lint;
 mod memoryvfs;
+mod module_cache;
+mod readonly;
+mod require_graph;
+mod resolver;
+#[cfg(feature = "signed-manifest")]
+mod signed_manifest;
+mod snapshot;
+mod source_transform;
+mod symlinks;
 mod utils;
 mod vfs_navigator;
 
 pub(crate) type Error = Box<dyn std::error::Error + Send + Sync>;
 
+pub use alias_hooks::AliasHooks;
+#[cfg(feature = "toml")]
+pub use asset_loader::TomlLoader;
+pub use asset_loader::{AssetLoader, JsonLoader, TextLoader};
 pub use asset_requirer::AssetRequirer;
+pub use bundler::{Bundle, bundle};
+pub use cache_key::CacheKeyStrategy;
+pub use dir_snapshot::SnapshotFilter;
+pub use events::RequireEvent;
 pub use fswrapper::FilesystemWrapper;
-pub use memoryvfs::{create_memory_vfs_from_map, create_vfs_from_map};
+pub use limits::{LimitError, ResourceLimits};
+pub use lockfile::{IntegrityError, LOCKFILE_VERSION, Lockfile};
+pub use luaurc_lint::{
+    LuaurcDiagnostic, LuaurcDiagnosticKind, LuaurcSeverity, lint_all_luaurc, lint_luaurc,
+};
+pub use memoryvfs::{
+    MemoryTreeBuilder, MemoryTreeError, create_memory_vfs_from_bytes_map,
+    create_memory_vfs_from_iter, create_memory_vfs_from_map, create_vfs_from_bytes_map,
+    create_vfs_from_iter, create_vfs_from_map,
+};
+pub use module_cache::{LoadedModule, ModuleCache};
+pub use readonly::ReadOnlyFS;
+pub use require_graph::{RequireGraph, StaticRequire, analyze_requires};
+pub use resolver::ResolveError;
+#[cfg(feature = "signed-manifest")]
+pub use signed_manifest::SignedManifestFS;
+pub use snapshot::{SNAPSHOT_VERSION, load_memory_snapshot, load_snapshot, save_snapshot};
+pub use source_transform::SourceTransform;
+pub use symlinks::{RootEscapeError, SymlinkEscapePolicy};
+pub use vfs_navigator::CaseSensitivity;
 
 // Re-export rust-vfs for convenience
-pub use vfs;
 pub use rust_embed;
 pub use rust_embed::Embed;
+pub use vfs;
 
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod tests;
@@ -0,0 +1,66 @@
+// Resource limits for module loading
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Limits on what an [`AssetRequirer`](crate::AssetRequirer) will load; `None` means unlimited
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Largest module file, in bytes, checked before the file is read
+    pub max_module_size: Option<u64>,
+    /// Most modules that may be executing at once, i.e. the longest chain of nested requires
+    pub max_require_depth: Option<usize>,
+    /// Most modules loaded over the requirer's lifetime
+    pub max_modules: Option<usize>,
+    /// Largest `.luaurc` or `.config.luau` file, in bytes, checked before the file is read
+    pub max_config_size: Option<u64>,
+}
+
+/// Error raised when loading a module would exceed a [`ResourceLimits`] limit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LimitError {
+    ModuleTooLarge { path: String, size: u64, limit: u64 },
+    RequireTooDeep { path: String, limit: usize },
+    TooManyModules { path: String, limit: usize },
+    ConfigTooLarge { path: String, size: u64, limit: u64 },
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitError::ModuleTooLarge { path, size, limit } => write!(
+                f,
+                "module {path} is {size} bytes, above the limit of {limit} bytes"
+            ),
+            LimitError::RequireTooDeep { path, limit } => write!(
+                f,
+                "requiring {path} exceeds the maximum require depth of {limit}"
+            ),
+            LimitError::TooManyModules { path, limit } => write!(
+                f,
+                "loading {path} exceeds the maximum of {limit} loaded modules"
+            ),
+            LimitError::ConfigTooLarge { path, size, limit } => write!(
+                f,
+                "config {path} is {size} bytes, above the limit of {limit} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// Counts a module as executing for as long as the guard is alive
+pub(crate) struct DepthGuard(Rc<Cell<usize>>);
+
+impl DepthGuard {
+    pub(crate) fn enter(depth: &Rc<Cell<usize>>) -> Self {
+        depth.set(depth.get() + 1);
+        Self(depth.clone())
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
@@ -0,0 +1,120 @@
+// Content integrity verification against a lockfile of SHA-256 hashes
+use super::cache_key::sha256_hex;
+use super::fswrapper::FilesystemWrapper;
+use std::collections::BTreeMap;
+
+/// Current version of the lockfile format
+pub const LOCKFILE_VERSION: u64 = 1;
+
+/// Expected SHA-256 hashes of files, keyed by absolute VFS path
+///
+/// Serialized as JSON:
+///
+/// ```json
+/// { "version": 1, "files": { "/main.luau": "<sha256 hex>", "/.luaurc": "<sha256 hex>" } }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    pub files: BTreeMap<String, String>,
+}
+
+/// Error raised when a file does not match a [`Lockfile`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// The file is not listed in the lockfile
+    Unlisted { path: String },
+    /// The file's contents do not match the hash in the lockfile
+    Mismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl std::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityError::Unlisted { path } => write!(f, "{path} is not listed in the lockfile"),
+            IntegrityError::Mismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{path} does not match the lockfile (expected sha256 {expected}, got {actual})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+impl Lockfile {
+    /// Hashes every file in `fs`
+    pub fn generate(fs: &FilesystemWrapper) -> Result<Self, crate::Error> {
+        let mut files = BTreeMap::new();
+        let mut dirs = vec![String::new()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs.read_dir(&dir)? {
+                let path = format!("{dir}/{entry}");
+                if fs.is_dir(path.clone())? {
+                    dirs.push(path);
+                } else {
+                    let contents = fs.get_file(path.clone())?;
+                    files.insert(path, sha256_hex(&contents));
+                }
+            }
+        }
+
+        Ok(Self { files })
+    }
+
+    pub fn from_json(bytes: &[u8]) -> Result<Self, crate::Error> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)?;
+        let version = value.get("version").and_then(|v| v.as_u64());
+        if version != Some(LOCKFILE_VERSION) {
+            return Err(format!("unsupported lockfile version {version:?}").into());
+        }
+
+        let files = value
+            .get("files")
+            .and_then(|f| f.as_object())
+            .ok_or("lockfile is missing its files table")?
+            .iter()
+            .map(|(path, hash)| match hash.as_str() {
+                Some(hash) => Ok((path.clone(), hash.to_lowercase())),
+                None => Err(format!("hash of {path} must be a string")),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { files })
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(&serde_json::json!({
+            "version": LOCKFILE_VERSION,
+            "files": self.files,
+        }))
+        .expect("Lockfile contents are always valid JSON")
+    }
+
+    /// Checks `contents` against the hash listed for the absolute VFS path `path`
+    pub fn verify(&self, path: &str, contents: &[u8]) -> Result<(), IntegrityError> {
+        let Some(expected) = self.files.get(path) else {
+            return Err(IntegrityError::Unlisted {
+                path: path.to_string(),
+            });
+        };
+
+        let actual = sha256_hex(contents);
+        if *expected != actual {
+            return Err(IntegrityError::Mismatch {
+                path: path.to_string(),
+                expected: expected.clone(),
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+// Parsing of `.luaurc` files with the syntax accepted by Luau's config parser
+//
+// Luau reads configs with its own lexer, so on top of JSON it accepts single-quoted strings,
+// `--` comments and trailing commas. String contents are used verbatim, without processing
+// escape sequences.
+use super::luaurc_lint::position;
+
+/// Maximum nesting depth of objects and arrays, which bounds the parser's recursion
+const MAX_DEPTH: usize = 128;
+
+pub(crate) enum Node {
+    Object(Vec<Entry>),
+    Array(Vec<Node>),
+    String(String),
+    Bool(bool),
+    /// A number, `null` or other bare word, none of which Luau accepts
+    Other(String),
+}
+
+pub(crate) struct Entry {
+    pub key: String,
+    pub key_offset: usize,
+    pub value: Node,
+    pub value_offset: usize,
+}
+
+impl Entry {
+    /// Creates an entry that does not come from a parsed file
+    pub fn new(key: &str, value: Node) -> Self {
+        Self {
+            key: key.to_string(),
+            key_offset: 0,
+            value,
+            value_offset: 0,
+        }
+    }
+}
+
+/// A syntax error in a `.luaurc` file, with a 1-based line and column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Parses the contents of a `.luaurc` file
+pub(crate) fn parse_luaurc(src: &[u8]) -> Result<Node, SyntaxError> {
+    let mut parser = Parser {
+        src,
+        pos: 0,
+        depth: 0,
+    };
+    let root = parser.parse_value()?;
+    parser.skip_trivia()?;
+    if parser.pos < src.len() {
+        return Err(parser.error(parser.pos, "expected end of file"));
+    }
+
+    Ok(root)
+}
+
+/// Serializes `node` in a form Luau's config parser reads back
+///
+/// Strings are read back identically unless they contain a quote, line break or trailing
+/// backslash, which gain a backslash so the string does not end early.
+pub(crate) fn write_luaurc(node: &Node, out: &mut Vec<u8>) {
+    match node {
+        Node::Object(entries) => {
+            out.push(b'{');
+            for (i, entry) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_string(&entry.key, out);
+                out.push(b':');
+                write_luaurc(&entry.value, out);
+            }
+            out.push(b'}');
+        }
+        Node::Array(values) => {
+            out.push(b'[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_luaurc(value, out);
+            }
+            out.push(b']');
+        }
+        Node::String(s) => write_string(s, out),
+        Node::Bool(b) => out.extend_from_slice(if *b { b"true" } else { b"false" }),
+        Node::Other(word) => out.extend_from_slice(word.as_bytes()),
+    }
+}
+
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    // Contents are read verbatim, so a backslash is only added where a quote, line break or
+    // trailing backslash would otherwise end the string early
+    out.push(b'"');
+    let mut backslashes = 0;
+    for b in s.bytes() {
+        if matches!(b, b'"' | b'\n' | b'\r') && backslashes % 2 == 0 {
+            out.push(b'\\');
+        }
+        backslashes = if b == b'\\' { backslashes + 1 } else { 0 };
+        out.push(b);
+    }
+    if backslashes % 2 == 1 {
+        out.push(b'\\');
+    }
+    out.push(b'"');
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, offset: usize, message: &str) -> SyntaxError {
+        let (line, column) = position(self.src, offset);
+        SyntaxError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Skips whitespace and comments
+    fn skip_trivia(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            match self.src.get(self.pos) {
+                Some(b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c) => self.pos += 1,
+                Some(b'-') if self.src.get(self.pos + 1) == Some(&b'-') => self.skip_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), SyntaxError> {
+        let start = self.pos;
+        self.pos += 2;
+
+        // Block comments open with a long bracket: `[`, any number of `=`, then `[`
+        let level = self.src[self.pos..]
+            .iter()
+            .skip(1)
+            .take_while(|b| **b == b'=')
+            .count();
+        if self.src.get(self.pos) == Some(&b'[')
+            && self.src.get(self.pos + level + 1) == Some(&b'[')
+        {
+            self.pos += level + 2;
+            let close = format!("]{}]", "=".repeat(level));
+            return match self.src[self.pos..]
+                .windows(close.len())
+                .position(|w| w == close.as_bytes())
+            {
+                Some(i) => {
+                    self.pos += i + close.len();
+                    Ok(())
+                }
+                None => Err(self.error(start, "unfinished long comment")),
+            };
+        }
+
+        while !matches!(self.src.get(self.pos), None | Some(b'\n')) {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<Node, SyntaxError> {
+        self.skip_trivia()?;
+        match self.src.get(self.pos) {
+            Some(b'{' | b'[') if self.depth >= MAX_DEPTH => Err(self.error(
+                self.pos,
+                &format!("nested more than {MAX_DEPTH} levels deep"),
+            )),
+            Some(b'{') => self.nested(Self::parse_object),
+            Some(b'[') => self.nested(Self::parse_array),
+            Some(b'"' | b'\'') => self.parse_string().map(Node::String),
+            Some(b) if b.is_ascii_alphanumeric() || *b == b'-' => {
+                let start = self.pos;
+                while matches!(
+                    self.src.get(self.pos),
+                    Some(b) if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.' | b'_')
+                ) {
+                    self.pos += 1;
+                }
+
+                let word = String::from_utf8_lossy(&self.src[start..self.pos]);
+                Ok(match word.as_ref() {
+                    "true" => Node::Bool(true),
+                    "false" => Node::Bool(false),
+                    _ => Node::Other(word.into_owned()),
+                })
+            }
+            _ => Err(self.error(self.pos, "expected a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Node, SyntaxError>,
+    ) -> Result<Node, SyntaxError> {
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_object(&mut self) -> Result<Node, SyntaxError> {
+        self.pos += 1;
+        let mut entries = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            match self.src.get(self.pos) {
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Node::Object(entries));
+                }
+                Some(b'"' | b'\'') => {}
+                _ => return Err(self.error(self.pos, "expected a key or '}'")),
+            }
+
+            let key_offset = self.pos;
+            let key = self.parse_string()?;
+            self.skip_trivia()?;
+            if self.src.get(self.pos) != Some(&b':') {
+                return Err(self.error(self.pos, "expected ':' after key"));
+            }
+            self.pos += 1;
+
+            self.skip_trivia()?;
+            let value_offset = self.pos;
+            let value = self.parse_value()?;
+            entries.push(Entry {
+                key,
+                key_offset,
+                value,
+                value_offset,
+            });
+
+            self.skip_trivia()?;
+            match self.src.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {}
+                _ => return Err(self.error(self.pos, "expected ',' or '}' after value")),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Node, SyntaxError> {
+        self.pos += 1;
+        let mut values = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.src.get(self.pos) == Some(&b']') {
+                self.pos += 1;
+                return Ok(Node::Array(values));
+            }
+
+            values.push(self.parse_value()?);
+
+            self.skip_trivia()?;
+            match self.src.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {}
+                _ => return Err(self.error(self.pos, "expected ',' or ']' after value")),
+            }
+        }
+    }
+
+    /// Parses a quoted string, returning its contents verbatim
+    fn parse_string(&mut self) -> Result<String, SyntaxError> {
+        let start = self.pos;
+        let quote = self.src[self.pos];
+        self.pos += 1;
+        loop {
+            match self.src.get(self.pos) {
+                None | Some(b'\n' | b'\r') => return Err(self.error(start, "unfinished string")),
+                Some(b'\\') => {
+                    // An escaped line break continues the string on the next line
+                    let escaped = &self.src[self.pos + 1..];
+                    self.pos += if escaped.starts_with(b"\r\n") { 3 } else { 2 };
+                }
+                Some(b) if *b == quote => break,
+                Some(_) => self.pos += 1,
+            }
+        }
+
+        self.pos += 1;
+        Ok(String::from_utf8_lossy(&self.src[start + 1..self.pos - 1]).into_owned())
+    }
+}
@@ -0,0 +1,287 @@
+// Validation of `.luaurc` files
+use super::fswrapper::FilesystemWrapper;
+use super::luaurc::{Node, parse_luaurc, write_luaurc};
+use super::utils::normalize_path;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LuaurcSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LuaurcDiagnosticKind {
+    /// The file cannot be parsed
+    ///
+    /// Configs are parsed like Luau does, which on top of JSON accepts single-quoted strings,
+    /// `--` comments and trailing commas.
+    InvalidJson,
+    /// The top level value is not an object
+    InvalidRoot,
+    /// The `aliases` field is not an object
+    InvalidAliases,
+    /// The alias name is empty, reserved or contains invalid characters
+    InvalidAliasName,
+    /// The alias target is not a string
+    InvalidAliasTarget,
+    /// The alias target does not exist in the filesystem
+    MissingAliasTarget,
+    /// The alias is defined more than once
+    DuplicateAlias,
+    /// The alias differs only in case from another alias in the same file
+    CaseCollidingAlias,
+}
+
+/// A problem found in a `.luaurc` file, with a 1-based line and column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LuaurcDiagnostic {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub severity: LuaurcSeverity,
+    pub kind: LuaurcDiagnosticKind,
+    pub message: String,
+}
+
+impl std::fmt::Display for LuaurcDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            LuaurcSeverity::Error => "error",
+            LuaurcSeverity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}:{}: {severity}: {}",
+            self.path, self.line, self.column, self.message
+        )
+    }
+}
+
+/// Lints the `.luaurc` file at `path`
+pub fn lint_luaurc(
+    fs: &FilesystemWrapper,
+    path: &str,
+) -> Result<Vec<LuaurcDiagnostic>, crate::Error> {
+    let contents = fs.get_file(path.to_string())?;
+    Ok(lint_luaurc_source(path, &contents, &|p| {
+        target_exists(fs, p)
+    }))
+}
+
+/// Lints every `.luaurc` file in the filesystem
+pub fn lint_all_luaurc(fs: &FilesystemWrapper) -> Result<Vec<LuaurcDiagnostic>, crate::Error> {
+    let mut diagnostics = Vec::new();
+    let mut dirs = vec![String::new()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs.read_dir(&dir)?.collect::<Vec<_>>();
+        entries.sort();
+        for entry in entries {
+            let path = format!("{dir}/{entry}");
+            if fs.is_dir(path.clone())? {
+                dirs.push(path);
+            } else if entry == ".luaurc" {
+                diagnostics.extend(lint_luaurc(fs, &path)?);
+            }
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+fn target_exists(fs: &FilesystemWrapper, path: &str) -> bool {
+    [
+        path.to_string(),
+        format!("{path}.luau"),
+        format!("{path}.lua"),
+    ]
+    .into_iter()
+    .any(|p| fs.is_file(p.clone()).unwrap_or(false) || fs.is_dir(p).unwrap_or(false))
+}
+
+/// Lints `.luaurc` contents, using `exists` to check whether alias targets exist
+pub(crate) fn lint_luaurc_source(
+    path: &str,
+    contents: &[u8],
+    exists: &dyn Fn(&str) -> bool,
+) -> Vec<LuaurcDiagnostic> {
+    let diagnostic = |offset: Option<usize>,
+                      severity: LuaurcSeverity,
+                      kind: LuaurcDiagnosticKind,
+                      message: String| {
+        let (line, column) = offset.map(|o| position(contents, o)).unwrap_or((1, 1));
+        LuaurcDiagnostic {
+            path: path.to_string(),
+            line,
+            column,
+            severity,
+            kind,
+            message,
+        }
+    };
+
+    let root = match parse_luaurc(contents) {
+        Ok(root) => root,
+        Err(e) => {
+            return vec![LuaurcDiagnostic {
+                path: path.to_string(),
+                line: e.line,
+                column: e.column,
+                severity: LuaurcSeverity::Error,
+                kind: LuaurcDiagnosticKind::InvalidJson,
+                message: format!("invalid config: {}", e.message),
+            }];
+        }
+    };
+
+    let Node::Object(root) = root else {
+        return vec![diagnostic(
+            Some(first_token(contents)),
+            LuaurcSeverity::Error,
+            LuaurcDiagnosticKind::InvalidRoot,
+            "config must be a JSON object".to_string(),
+        )];
+    };
+
+    let mut diagnostics = Vec::new();
+    let config_dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+    for entry in root.iter().filter(|e| e.key == "aliases") {
+        let Node::Object(aliases) = &entry.value else {
+            diagnostics.push(diagnostic(
+                Some(entry.value_offset),
+                LuaurcSeverity::Error,
+                LuaurcDiagnosticKind::InvalidAliases,
+                "aliases must be an object".to_string(),
+            ));
+            continue;
+        };
+
+        for (i, alias) in aliases.iter().enumerate() {
+            let earlier = &aliases[..i];
+            if earlier.iter().any(|e| e.key == alias.key) {
+                diagnostics.push(diagnostic(
+                    Some(alias.key_offset),
+                    LuaurcSeverity::Error,
+                    LuaurcDiagnosticKind::DuplicateAlias,
+                    format!("alias {:?} is defined more than once", alias.key),
+                ));
+            } else if let Some(other) = earlier
+                .iter()
+                .find(|e| e.key.eq_ignore_ascii_case(&alias.key))
+            {
+                diagnostics.push(diagnostic(
+                    Some(alias.key_offset),
+                    LuaurcSeverity::Error,
+                    LuaurcDiagnosticKind::CaseCollidingAlias,
+                    format!(
+                        "alias {:?} collides with {:?}; aliases are case-insensitive",
+                        alias.key, other.key
+                    ),
+                ));
+            }
+
+            if let Some(reason) = invalid_alias_name(&alias.key) {
+                diagnostics.push(diagnostic(
+                    Some(alias.key_offset),
+                    LuaurcSeverity::Error,
+                    LuaurcDiagnosticKind::InvalidAliasName,
+                    format!("alias {:?} {reason}", alias.key),
+                ));
+            }
+
+            let Node::String(target) = &alias.value else {
+                diagnostics.push(diagnostic(
+                    Some(alias.value_offset),
+                    LuaurcSeverity::Error,
+                    LuaurcDiagnosticKind::InvalidAliasTarget,
+                    format!(
+                        "target of alias {:?} must be a string, found {}",
+                        alias.key,
+                        describe(&alias.value)
+                    ),
+                ));
+                continue;
+            };
+
+            // Targets pointing at other aliases may be defined by a parent config
+            if target.starts_with('@') {
+                continue;
+            }
+
+            let resolved = if target.starts_with('/') {
+                target.clone()
+            } else {
+                format!("{config_dir}/{target}")
+            };
+            let resolved = normalize_path(&PathBuf::from(resolved))
+                .to_string_lossy()
+                .to_string();
+
+            if resolved.starts_with("..") || !exists(&resolved) {
+                diagnostics.push(diagnostic(
+                    Some(alias.value_offset),
+                    LuaurcSeverity::Warning,
+                    LuaurcDiagnosticKind::MissingAliasTarget,
+                    format!(
+                        "target {target:?} of alias {:?} does not exist (resolved to {resolved})",
+                        alias.key
+                    ),
+                ));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Returns why `name` is not a valid alias name, if it isn't
+fn invalid_alias_name(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        return Some("must not be empty");
+    }
+
+    if name.eq_ignore_ascii_case("self") {
+        return Some("is reserved");
+    }
+
+    if name == "." || name == ".." {
+        return Some("must not be a relative path component");
+    }
+
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Some("may only contain letters, digits, '-', '_' and '.'");
+    }
+
+    None
+}
+
+/// Renders a value for a diagnostic message
+fn describe(node: &Node) -> String {
+    let mut out = Vec::new();
+    write_luaurc(node, &mut out);
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Returns the offset of the first character that is not whitespace
+fn first_token(contents: &[u8]) -> usize {
+    contents
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(0)
+}
+
+/// Converts a byte offset into a 1-based line and column
+pub(crate) fn position(src: &[u8], offset: usize) -> (usize, usize) {
+    let before = &src[..offset.min(src.len())];
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    (line, offset - line_start + 1)
+}
@@ -1,5 +1,5 @@
 use super::FilesystemWrapper;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use vfs::FileSystem;
 
 /// Creates a virtual filesystem from a map of paths to content.
@@ -14,9 +14,50 @@ pub fn create_vfs_from_map(
 pub fn create_memory_vfs_from_map(
     tree: &HashMap<String, String>,
 ) -> Result<vfs::MemoryFS, crate::Error> {
+    create_memory_vfs_from_iter(sorted(tree))
+}
+
+/// Creates a virtual filesystem from a map of paths to binary content.
+pub fn create_vfs_from_bytes_map(
+    tree: &HashMap<String, Vec<u8>>,
+) -> Result<FilesystemWrapper, crate::Error> {
+    let mem_fs = create_memory_vfs_from_bytes_map(tree)?;
+    Ok(FilesystemWrapper::new(mem_fs))
+}
+
+/// Creates a virtual filesystem that is a concrete MemoryFS from a map of paths to binary content.
+pub fn create_memory_vfs_from_bytes_map(
+    tree: &HashMap<String, Vec<u8>>,
+) -> Result<vfs::MemoryFS, crate::Error> {
+    create_memory_vfs_from_iter(sorted(tree))
+}
+
+/// Creates a virtual filesystem from `(path, content)` pairs.
+pub fn create_vfs_from_iter<P, C>(
+    files: impl IntoIterator<Item = (P, C)>,
+) -> Result<FilesystemWrapper, crate::Error>
+where
+    P: AsRef<str>,
+    C: AsRef<[u8]>,
+{
+    let mem_fs = create_memory_vfs_from_iter(files)?;
+    Ok(FilesystemWrapper::new(mem_fs))
+}
+
+/// Creates a virtual filesystem that is a concrete MemoryFS from `(path, content)` pairs.
+///
+/// Files are created in iteration order.
+pub fn create_memory_vfs_from_iter<P, C>(
+    files: impl IntoIterator<Item = (P, C)>,
+) -> Result<vfs::MemoryFS, crate::Error>
+where
+    P: AsRef<str>,
+    C: AsRef<[u8]>,
+{
     let fs = vfs::MemoryFS::new();
     let mut created = HashSet::new();
-    for (path_s, content) in tree {
+    for (path_s, content) in files {
+        let path_s = path_s.as_ref();
         let path = path_s.split('/').collect::<Vec<_>>();
         if path.len() >= 2 {
             // Folder part is everything except the last part
@@ -42,9 +83,189 @@ pub fn create_memory_vfs_from_map(
         let path_s = format!("/{path_s}");
         fs.create_file(&path_s)
             .map_err(|e| crate::Error::from(format!("Failed to create file {path_s}: {e}")))?
-            .write_all(content.as_bytes())
+            .write_all(content.as_ref())
             .map_err(|e| crate::Error::from(format!("Failed to write to {path_s}: {e}")))?;
     }
 
     Ok(fs)
 }
+
+/// Orders a map's entries by path so that creation order does not depend on hashing
+fn sorted<V>(tree: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries = tree.iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryTreeError {
+    /// The path is empty or escapes the root
+    InvalidPath { path: String, reason: &'static str },
+    /// The same file was added more than once
+    DuplicateFile(String),
+    /// `path` needs `file` to be a directory, or a directory was added where a file already is
+    FileDirectoryConflict { file: String, path: String },
+    /// The filesystem rejected an operation
+    Vfs(String),
+}
+
+impl std::fmt::Display for MemoryTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryTreeError::InvalidPath { path, reason } => {
+                write!(f, "invalid path {path:?}: {reason}")
+            }
+            MemoryTreeError::DuplicateFile(path) => {
+                write!(f, "file /{path} was added more than once")
+            }
+            MemoryTreeError::FileDirectoryConflict { file, path } => {
+                write!(f, "/{path} conflicts with the file /{file}")
+            }
+            MemoryTreeError::Vfs(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryTreeError {}
+
+#[derive(Debug, Clone)]
+enum TreeEntry {
+    File(Vec<u8>),
+    Dir,
+}
+
+/// Builds a MemoryFS from files and directories, normalising paths and rejecting conflicts.
+///
+/// Paths may use `/` or `\` separators, a leading `/` or `./`, repeated separators and `.`/`..`
+/// components, as long as they stay inside the root. Problems are reported by [`build`] in the
+/// order entries were added.
+///
+/// [`build`]: MemoryTreeBuilder::build
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTreeBuilder {
+    entries: Vec<(String, TreeEntry)>,
+}
+
+impl MemoryTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, creating its parent directories
+    pub fn with_file(mut self, path: &str, contents: impl Into<Vec<u8>>) -> Self {
+        self.entries
+            .push((path.to_string(), TreeEntry::File(contents.into())));
+        self
+    }
+
+    /// Adds a directory, which may be left empty
+    pub fn with_dir(mut self, path: &str) -> Self {
+        self.entries.push((path.to_string(), TreeEntry::Dir));
+        self
+    }
+
+    /// Validates the tree and creates it as a concrete MemoryFS
+    pub fn build(self) -> Result<vfs::MemoryFS, MemoryTreeError> {
+        let mut tree: BTreeMap<String, TreeEntry> = BTreeMap::new();
+        for (path, entry) in self.entries {
+            let normalized = normalize_tree_path(&path)?;
+            let components = normalized.split('/').collect::<Vec<_>>();
+
+            for i in 1..components.len() {
+                let parent = components[..i].join("/");
+                match tree.get(&parent) {
+                    Some(TreeEntry::File(_)) => {
+                        return Err(MemoryTreeError::FileDirectoryConflict {
+                            file: parent,
+                            path: normalized,
+                        });
+                    }
+                    Some(TreeEntry::Dir) => {}
+                    None => {
+                        tree.insert(parent, TreeEntry::Dir);
+                    }
+                }
+            }
+
+            match (tree.get(&normalized), &entry) {
+                (None, _) | (Some(TreeEntry::Dir), TreeEntry::Dir) => {}
+                (Some(TreeEntry::File(_)), TreeEntry::File(_)) => {
+                    return Err(MemoryTreeError::DuplicateFile(normalized));
+                }
+                (Some(TreeEntry::File(_)), TreeEntry::Dir) => {
+                    return Err(MemoryTreeError::FileDirectoryConflict {
+                        file: normalized.clone(),
+                        path: normalized,
+                    });
+                }
+                (Some(TreeEntry::Dir), TreeEntry::File(_)) => {
+                    // Report the first file already inside the directory
+                    let prefix = format!("{normalized}/");
+                    let inside = tree
+                        .keys()
+                        .find(|p| p.starts_with(&prefix))
+                        .cloned()
+                        .unwrap_or_else(|| normalized.clone());
+                    return Err(MemoryTreeError::FileDirectoryConflict {
+                        file: normalized,
+                        path: inside,
+                    });
+                }
+            }
+
+            tree.insert(normalized, entry);
+        }
+
+        // Sorting by path places directories before their children
+        let fs = vfs::MemoryFS::new();
+        for (path, entry) in tree {
+            let path = format!("/{path}");
+            match entry {
+                TreeEntry::Dir => fs.create_dir(&path).map_err(|e| {
+                    MemoryTreeError::Vfs(format!("Failed to create directory {path}: {e}"))
+                })?,
+                TreeEntry::File(contents) => fs
+                    .create_file(&path)
+                    .map_err(|e| {
+                        MemoryTreeError::Vfs(format!("Failed to create file {path}: {e}"))
+                    })?
+                    .write_all(&contents)
+                    .map_err(|e| MemoryTreeError::Vfs(format!("Failed to write to {path}: {e}")))?,
+            }
+        }
+
+        Ok(fs)
+    }
+
+    /// Validates the tree and creates it as a virtual filesystem
+    pub fn build_wrapper(self) -> Result<FilesystemWrapper, MemoryTreeError> {
+        Ok(FilesystemWrapper::new(self.build()?))
+    }
+}
+
+/// Normalises a tree path to its components joined by `/`, without a leading `/`
+fn normalize_tree_path(path: &str) -> Result<String, MemoryTreeError> {
+    let invalid = |reason| MemoryTreeError::InvalidPath {
+        path: path.to_string(),
+        reason,
+    };
+
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(invalid("escapes the root"));
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+
+    if components.is_empty() {
+        return Err(invalid("does not name a file or directory"));
+    }
+
+    Ok(components.join("/"))
+}
@@ -0,0 +1,129 @@
+use mluau::prelude::*;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+/// Registry key of the table Luau caches required modules in
+const MODULES_KEY: &str = "_MODULES";
+
+/// A module loaded through an [`AssetRequirer`](crate::AssetRequirer)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadedModule {
+    pub cache_key: String,
+    pub path: String,
+    pub loaded_at: SystemTime,
+}
+
+/// Handle to the modules an [`AssetRequirer`](crate::AssetRequirer) has loaded
+///
+/// Obtained with [`AssetRequirer::module_cache`](crate::AssetRequirer::module_cache) before the
+/// requirer is moved into `create_require_function`; it stays connected to the requirer.
+#[derive(Clone, Default)]
+pub struct ModuleCache {
+    modules: Rc<RefCell<Vec<LoadedModule>>>,
+}
+
+impl ModuleCache {
+    pub(crate) fn record(&self, cache_key: String, path: String) {
+        let mut modules = self.modules.borrow_mut();
+        modules.retain(|m| m.cache_key != cache_key);
+        modules.push(LoadedModule {
+            cache_key,
+            path,
+            loaded_at: SystemTime::now(),
+        });
+    }
+
+    fn modules_table(lua: &Lua) -> LuaResult<Option<LuaTable>> {
+        lua.named_registry_value::<Option<LuaTable>>(MODULES_KEY)
+    }
+
+    /// Returns the modules currently in the Luau require cache, in load order
+    ///
+    /// Modules whose loading failed, or that were removed from the cache, are not included.
+    pub fn loaded(&self, lua: &Lua) -> LuaResult<Vec<LoadedModule>> {
+        let Some(table) = Self::modules_table(lua)? else {
+            return Ok(Vec::new());
+        };
+
+        let mut loaded = Vec::new();
+        for module in self.modules.borrow().iter() {
+            if !table
+                .raw_get::<LuaValue>(module.cache_key.as_str())?
+                .is_nil()
+            {
+                loaded.push(module.clone());
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Removes the module with the given cache key from the require cache, so the next require
+    /// loads it again. Returns whether the module was cached
+    pub fn unload(&self, lua: &Lua, cache_key: &str) -> LuaResult<bool> {
+        self.modules
+            .borrow_mut()
+            .retain(|m| m.cache_key != cache_key);
+
+        let Some(table) = Self::modules_table(lua)? else {
+            return Ok(false);
+        };
+
+        let was_cached = !table.raw_get::<LuaValue>(cache_key)?.is_nil();
+        table.raw_set(cache_key, LuaValue::Nil)?;
+        Ok(was_cached)
+    }
+
+    /// Removes every module cached under `cache_prefix` from the require cache, including those
+    /// loaded by other requirers sharing the prefix, returning how many were removed
+    ///
+    /// Keys are matched as `{cache_prefix}@{absolute_path}...` or
+    /// `{cache_prefix}#sha256:{hash}`, the forms produced by the built-in
+    /// [`CacheKeyStrategy`](crate::CacheKeyStrategy) variants, so a prefix such as `cache@x`
+    /// is not swept when clearing `cache`.
+    pub fn clear(&self, lua: &Lua, cache_prefix: &str) -> LuaResult<usize> {
+        let has_prefix = |key: &str| is_builtin_key(key, cache_prefix);
+
+        self.modules
+            .borrow_mut()
+            .retain(|m| !has_prefix(&m.cache_key));
+
+        let Some(table) = Self::modules_table(lua)? else {
+            return Ok(0);
+        };
+
+        let mut keys = Vec::new();
+        for pair in table.pairs::<LuaValue, LuaValue>() {
+            if let (LuaValue::String(key), _) = pair?
+                && has_prefix(&key.to_string_lossy())
+            {
+                keys.push(key);
+            }
+        }
+
+        let removed = keys.len();
+        for key in keys {
+            table.raw_set(key, LuaValue::Nil)?;
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Whether `key` has one of the forms the built-in cache key strategies produce for
+/// `cache_prefix`
+fn is_builtin_key(key: &str, cache_prefix: &str) -> bool {
+    let Some(rest) = key.strip_prefix(cache_prefix) else {
+        return false;
+    };
+
+    if let Some(hash) = rest.strip_prefix("#sha256:") {
+        return hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit());
+    }
+
+    // Paths are either VFS paths or canonicalised host paths, both absolute
+    rest.strip_prefix('@')
+        .is_some_and(|path| path.starts_with('/') || Path::new(path).is_absolute())
+}
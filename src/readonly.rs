@@ -0,0 +1,90 @@
+// Read-only view over a filesystem
+use std::time::SystemTime;
+use vfs::error::VfsErrorKind;
+use vfs::path::{SeekAndRead, SeekAndWrite, VfsMetadata};
+use vfs::{FileSystem, VfsError, VfsResult};
+
+/// A filesystem that forwards reads to an inner filesystem and rejects every mutating operation
+///
+/// [`AssetRequirer`](crate::AssetRequirer) only ever reads, so a requirer can be backed by the
+/// same read-only filesystem that is handed to untrusted code.
+#[derive(Debug)]
+pub struct ReadOnlyFS {
+    inner: Box<dyn FileSystem>,
+}
+
+impl ReadOnlyFS {
+    pub fn new<T: FileSystem>(fs: T) -> Self {
+        Self {
+            inner: Box::new(fs),
+        }
+    }
+}
+
+pub(crate) fn read_only<T>(path: &str) -> VfsResult<T> {
+    Err(VfsError::from(VfsErrorKind::Other(format!(
+        "cannot modify {path}: filesystem is read-only"
+    ))))
+}
+
+impl FileSystem for ReadOnlyFS {
+    fn read_dir(&self, path: &str) -> VfsResult<Box<dyn Iterator<Item = String> + Send>> {
+        self.inner.read_dir(path)
+    }
+
+    fn create_dir(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn open_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndRead + Send>> {
+        self.inner.open_file(path)
+    }
+
+    fn create_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        read_only(path)
+    }
+
+    fn append_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        read_only(path)
+    }
+
+    fn metadata(&self, path: &str) -> VfsResult<VfsMetadata> {
+        self.inner.metadata(path)
+    }
+
+    fn set_creation_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn set_modification_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn set_access_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn exists(&self, path: &str) -> VfsResult<bool> {
+        self.inner.exists(path)
+    }
+
+    fn remove_file(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn remove_dir(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn copy_file(&self, _src: &str, dest: &str) -> VfsResult<()> {
+        read_only(dest)
+    }
+
+    fn move_file(&self, src: &str, _dest: &str) -> VfsResult<()> {
+        read_only(src)
+    }
+
+    fn move_dir(&self, src: &str, _dest: &str) -> VfsResult<()> {
+        read_only(src)
+    }
+}
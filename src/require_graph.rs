@@ -0,0 +1,418 @@
+// Static analysis of string-literal requires, without executing any code
+use super::asset_requirer::AssetRequirer;
+use super::fswrapper::FilesystemWrapper;
+use super::luaurc_lint::position;
+use super::resolver::{ResolveError, resolve_require};
+use std::collections::{BTreeMap, VecDeque};
+
+/// A string-literal `require` call found in a module
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticRequire {
+    /// The path passed to `require`
+    pub path: String,
+    /// 1-based line of the path literal
+    pub line: usize,
+    /// 1-based column of the path literal
+    pub column: usize,
+    /// The absolute VFS path of the required module, or why it could not be resolved
+    pub resolved: Result<String, ResolveError>,
+}
+
+/// The static dependency graph of the modules in a filesystem
+///
+/// Only requires whose argument is a single string literal are analyzed; dynamic requires are
+/// skipped. Sources are scanned as stored, before any [`SourceTransform`](crate::SourceTransform).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequireGraph {
+    /// The requires made by each module, keyed by the absolute VFS path of the module
+    pub modules: BTreeMap<String, Vec<StaticRequire>>,
+}
+
+impl RequireGraph {
+    /// Returns the modules `module` successfully requires
+    pub fn dependencies(&self, module: &str) -> Vec<&str> {
+        self.modules
+            .get(module)
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.resolved.as_deref().ok())
+            .collect()
+    }
+
+    /// Returns every require that could not be resolved, other than ambiguous ones
+    pub fn unresolved(&self) -> Vec<(&str, &StaticRequire)> {
+        self.requires()
+            .filter(|(_, r)| {
+                r.resolved
+                    .as_ref()
+                    .is_err_and(|e| !matches!(e, ResolveError::Ambiguous { .. }))
+            })
+            .collect()
+    }
+
+    /// Returns every require matching more than one module
+    pub fn ambiguous(&self) -> Vec<(&str, &StaticRequire)> {
+        self.requires()
+            .filter(|(_, r)| matches!(r.resolved, Err(ResolveError::Ambiguous { .. })))
+            .collect()
+    }
+
+    /// Whether every require in the graph resolved
+    pub fn is_ok(&self) -> bool {
+        self.requires().all(|(_, r)| r.resolved.is_ok())
+    }
+
+    fn requires(&self) -> impl Iterator<Item = (&str, &StaticRequire)> {
+        self.modules
+            .iter()
+            .flat_map(|(module, requires)| requires.iter().map(move |r| (module.as_str(), r)))
+    }
+}
+
+/// Builds the static require graph of every Luau module in `fs`, using `.luaurc` aliases only
+pub fn analyze_requires(fs: &FilesystemWrapper) -> Result<RequireGraph, crate::Error> {
+    let lua = mluau::Lua::new();
+    let requirer = AssetRequirer::new(fs.clone(), "analyze".to_string(), lua.globals());
+    build_require_graph(&requirer)
+}
+
+/// Builds the require graph from every Luau module in the requirer's filesystem, following
+/// resolved requires into mounted filesystems
+pub(crate) fn build_require_graph(requirer: &AssetRequirer) -> Result<RequireGraph, crate::Error> {
+    let roots = luau_files(requirer)?;
+    build_require_graph_from(requirer, roots)
+}
+
+/// Builds the require graph of the modules reachable from `roots`
+pub(crate) fn build_require_graph_from(
+    requirer: &AssetRequirer,
+    roots: Vec<String>,
+) -> Result<RequireGraph, crate::Error> {
+    let mut requirer = requirer.detached();
+    let mut graph = RequireGraph::default();
+    let mut queue = roots.into_iter().collect::<VecDeque<_>>();
+
+    while let Some(module) = queue.pop_front() {
+        if graph.modules.contains_key(&module) {
+            continue;
+        }
+
+        let source = requirer.navigator().get_file(module.clone())?;
+        let mut requires = Vec::new();
+        for (path, offset) in find_requires(&source) {
+            let (line, column) = position(&source, offset);
+            let resolved = resolve_require(&mut requirer, &module, &path);
+            if let Ok(target) = &resolved
+                && is_luau_file(target)
+            {
+                queue.push_back(target.clone());
+            }
+
+            requires.push(StaticRequire {
+                path,
+                line,
+                column,
+                resolved,
+            });
+        }
+
+        graph.modules.insert(module, requires);
+    }
+
+    Ok(graph)
+}
+
+pub(crate) fn is_luau_file(path: &str) -> bool {
+    path.ends_with(".luau") || path.ends_with(".lua")
+}
+
+/// Lists every Luau file in the requirer's main filesystem, sorted
+fn luau_files(requirer: &AssetRequirer) -> Result<Vec<String>, crate::Error> {
+    let navigator = requirer.navigator();
+    let mut files = Vec::new();
+    let mut dirs = vec![String::new()];
+    while let Some(dir) = dirs.pop() {
+        let listed = if dir.is_empty() { "/" } else { dir.as_str() };
+        for entry in navigator.read_dir(listed.to_string())? {
+            let path = format!("{dir}/{entry}");
+            if navigator.is_dir(path.clone())? {
+                dirs.push(path);
+            } else if is_luau_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Finds `require("...")` and `require "..."` calls with a string-literal argument, returning
+/// each path along with the byte offset of its literal
+pub(crate) fn find_requires(src: &[u8]) -> Vec<(String, usize)> {
+    let mut lexer = Lexer { src, pos: 0 };
+    let mut requires = Vec::new();
+    let mut previous = None;
+
+    while let Some(token) = lexer.next_token() {
+        // Skip field and method calls such as `x.require("y")`
+        let is_call = token == Token::Ident("require") && !matches!(previous, Some(Token::Dot));
+        previous = Some(token);
+        if !is_call {
+            continue;
+        }
+
+        let checkpoint = lexer.pos;
+        let parenthesised = lexer.next_token() == Some(Token::LParen);
+        if !parenthesised {
+            lexer.pos = checkpoint;
+        }
+
+        let offset = lexer.skip_trivia();
+        let Some(Token::String(Some(path))) = lexer.next_token() else {
+            lexer.pos = checkpoint;
+            continue;
+        };
+
+        if parenthesised && lexer.next_token() != Some(Token::RParen) {
+            lexer.pos = checkpoint;
+            continue;
+        }
+
+        requires.push((path, offset));
+        previous = None;
+    }
+
+    requires
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    /// A string literal, or `None` for an interpolated or malformed one
+    String(Option<String>),
+    /// `.` or `:`
+    Dot,
+    LParen,
+    RParen,
+    Other,
+}
+
+/// Minimal Luau lexer that understands enough of the grammar to skip comments and strings
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self, n: usize) -> Option<u8> {
+        self.src.get(self.pos + n).copied()
+    }
+
+    /// Skips whitespace and comments, returning the offset of the next token
+    fn skip_trivia(&mut self) -> usize {
+        loop {
+            match self.peek(0) {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.pos += 1,
+                Some(b'-') if self.peek(1) == Some(b'-') => {
+                    self.pos += 2;
+                    if self.long_bracket_level().is_some() {
+                        self.long_bracket();
+                    } else {
+                        while !matches!(self.peek(0), None | Some(b'\n')) {
+                            self.pos += 1;
+                        }
+                    }
+                }
+                _ => return self.pos,
+            }
+        }
+    }
+
+    /// Returns the level of a long bracket (`[[`, `[=[`, ...) starting at the current position
+    fn long_bracket_level(&self) -> Option<usize> {
+        if self.peek(0) != Some(b'[') {
+            return None;
+        }
+
+        let mut level = 0;
+        while self.peek(1 + level) == Some(b'=') {
+            level += 1;
+        }
+
+        (self.peek(1 + level) == Some(b'[')).then_some(level)
+    }
+
+    /// Consumes a long bracket, returning its contents
+    fn long_bracket(&mut self) -> String {
+        let level = self.long_bracket_level().unwrap_or(0);
+        self.pos += level + 2;
+        let start = self.pos;
+        let close = format!("]{}]", "=".repeat(level));
+        let end = self.src[start..]
+            .windows(close.len())
+            .position(|w| w == close.as_bytes())
+            .map(|p| start + p)
+            .unwrap_or(self.src.len());
+        self.pos = (end + close.len()).min(self.src.len());
+        String::from_utf8_lossy(&self.src[start..end]).into_owned()
+    }
+
+    /// Consumes a quoted string, returning its contents with escapes decoded
+    ///
+    /// Returns `None` for interpolated strings and strings with a malformed escape
+    fn quoted_string(&mut self) -> Option<String> {
+        let quote = self.src[self.pos];
+        self.pos += 1;
+        let mut contents = Vec::new();
+        let mut valid = true;
+        while let Some(b) = self.peek(0) {
+            self.pos += 1;
+            match b {
+                b'\\' => valid &= self.escape(quote, &mut contents),
+                b'{' if quote == b'`' => valid = false,
+                b'\n' if quote != b'`' => break,
+                _ if b == quote => break,
+                _ => contents.push(b),
+            }
+        }
+
+        valid.then(|| String::from_utf8_lossy(&contents).into_owned())
+    }
+
+    /// Decodes the escape sequence after a `\\`, returning whether it is valid
+    fn escape(&mut self, quote: u8, contents: &mut Vec<u8>) -> bool {
+        let Some(b) = self.peek(0) else {
+            return false;
+        };
+        self.pos += 1;
+        let decoded = match b {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0b,
+            b'\\' | b'"' | b'\'' => b,
+            b'{' | b'`' if quote == b'`' => b,
+            b'\n' => {
+                if self.peek(0) == Some(b'\r') {
+                    self.pos += 1;
+                }
+                b'\n'
+            }
+            b'\r' => {
+                if self.peek(0) == Some(b'\n') {
+                    self.pos += 1;
+                }
+                b'\n'
+            }
+            b'z' => {
+                while self
+                    .peek(0)
+                    .is_some_and(|b| b.is_ascii_whitespace() || b == 0x0b)
+                {
+                    self.pos += 1;
+                }
+                return true;
+            }
+            b'x' => {
+                let digits = self.src.get(self.pos..self.pos + 2);
+                let Some(value) = digits
+                    .filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|d| u8::from_str_radix(std::str::from_utf8(d).ok()?, 16).ok())
+                else {
+                    return false;
+                };
+                self.pos += 2;
+                value
+            }
+            b'0'..=b'9' => {
+                let mut value = u32::from(b - b'0');
+                for _ in 0..2 {
+                    match self.peek(0) {
+                        Some(d @ b'0'..=b'9') => {
+                            value = value * 10 + u32::from(d - b'0');
+                            self.pos += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                let Ok(value) = u8::try_from(value) else {
+                    return false;
+                };
+                value
+            }
+            b'u' => {
+                if self.peek(0) != Some(b'{') {
+                    return false;
+                }
+                let start = self.pos + 1;
+                let Some(len) = self.src[start..].iter().position(|b| *b == b'}') else {
+                    return false;
+                };
+                let digits = &self.src[start..start + len];
+                let Some(c) = std::str::from_utf8(digits)
+                    .ok()
+                    .filter(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|d| u32::from_str_radix(d, 16).ok())
+                    .and_then(char::from_u32)
+                else {
+                    return false;
+                };
+                self.pos = start + len + 1;
+                contents.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return true;
+            }
+            _ => return false,
+        };
+
+        contents.push(decoded);
+        true
+    }
+
+    fn next_token(&mut self) -> Option<Token<'a>> {
+        self.skip_trivia();
+        let b = self.peek(0)?;
+        let token = match b {
+            b'"' | b'\'' | b'`' => Token::String(self.quoted_string()),
+            b'[' if self.long_bracket_level().is_some() => Token::String(Some(self.long_bracket())),
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let start = self.pos;
+                while matches!(
+                    self.peek(0),
+                    Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_')
+                ) {
+                    self.pos += 1;
+                }
+                let src: &'a [u8] = self.src;
+                Token::Ident(std::str::from_utf8(&src[start..self.pos]).unwrap_or_default())
+            }
+            b'.' if self.peek(1) == Some(b'.') => {
+                while self.peek(0) == Some(b'.') {
+                    self.pos += 1;
+                }
+                Token::Other
+            }
+            b'.' | b':' => {
+                self.pos += 1;
+                Token::Dot
+            }
+            b'(' => {
+                self.pos += 1;
+                Token::LParen
+            }
+            b')' => {
+                self.pos += 1;
+                Token::RParen
+            }
+            _ => {
+                self.pos += 1;
+                Token::Other
+            }
+        };
+
+        Some(token)
+    }
+}
@@ -0,0 +1,216 @@
+// Require-by-string resolution driven from Rust, mirroring Luau's Require/Navigator
+use super::asset_requirer::AssetRequirer;
+use super::luaurc::{Node, parse_luaurc};
+use super::symlinks::RootEscapeError;
+use mluau::prelude::*;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveError {
+    /// The require path does not start with `./`, `../` or `@`
+    InvalidPath(String),
+    /// No `.luaurc` in scope defines the alias
+    UnknownAlias(String),
+    /// The path does not point to a module, along with the candidate paths checked
+    NotFound {
+        probes: Vec<String>,
+    },
+    /// The path matches more than one module, along with the candidate paths checked
+    Ambiguous {
+        probes: Vec<String>,
+    },
+    /// The path leaves the root set with
+    /// [`AssetRequirer::with_root_jail`](crate::AssetRequirer::with_root_jail)
+    EscapesRoot(String),
+    Other(String),
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::InvalidPath(path) => write!(
+                f,
+                "require path {path:?} must start with a valid prefix: ./, ../, or @"
+            ),
+            ResolveError::UnknownAlias(alias) => write!(f, "@{alias} is not a valid alias"),
+            ResolveError::NotFound { probes } => {
+                write!(f, "could not resolve module (tried: {})", probes.join(", "))
+            }
+            ResolveError::Ambiguous { probes } => {
+                write!(f, "module path is ambiguous (tried: {})", probes.join(", "))
+            }
+            ResolveError::EscapesRoot(path) => write!(f, "{path} escapes the filesystem root"),
+            ResolveError::Other(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+/// Maximum number of alias-to-alias hops before giving up
+const MAX_ALIAS_DEPTH: usize = 16;
+
+fn nav_result(
+    req: &AssetRequirer,
+    result: Result<(), LuaNavigateError>,
+) -> Result<(), ResolveError> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(LuaNavigateError::NotFound) => Err(ResolveError::NotFound {
+            probes: req.probes().to_vec(),
+        }),
+        Err(LuaNavigateError::Ambiguous) => Err(ResolveError::Ambiguous {
+            probes: req.probes().to_vec(),
+        }),
+        Err(LuaNavigateError::Other(e)) => match e.downcast_ref::<RootEscapeError>() {
+            Some(escape) => Err(ResolveError::EscapesRoot(escape.path.clone())),
+            None => Err(ResolveError::Other(e.to_string())),
+        },
+    }
+}
+
+/// Splits a require path into its components, skipping empty and `.` components
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|c| !c.is_empty() && *c != ".")
+}
+
+/// Walks `components` from the current navigator position
+fn navigate_through(req: &mut AssetRequirer, path: &str) -> Result<(), ResolveError> {
+    for component in components(path) {
+        let result = if component == ".." {
+            // The navigator cannot go above the root, so this would otherwise be a NotFound
+            if req.is_at_jail_root() {
+                return Err(ResolveError::EscapesRoot(path.to_string()));
+            }
+            req.to_parent()
+        } else {
+            req.to_child(component)
+        };
+        nav_result(req, result)?;
+    }
+
+    Ok(())
+}
+
+/// Parses the `aliases` table of a `.luaurc` file, lowercasing alias names
+///
+/// Accepts the same syntax as Luau, including comments and trailing commas.
+pub(crate) fn parse_luaurc_aliases(
+    contents: &[u8],
+) -> Result<HashMap<String, String>, crate::Error> {
+    let Node::Object(root) = parse_luaurc(contents)? else {
+        return Err("config must be an object".into());
+    };
+
+    let mut aliases = HashMap::new();
+    for entry in root.iter().filter(|e| e.key == "aliases") {
+        let Node::Object(map) = &entry.value else {
+            continue;
+        };
+
+        for alias in map {
+            if let Node::String(target) = &alias.value {
+                aliases.insert(alias.key.to_lowercase(), target.clone());
+            }
+        }
+    }
+
+    Ok(aliases)
+}
+
+/// Walks upwards from the requirer until a config defining `alias` is found.
+///
+/// On success, the navigator is left positioned at the directory containing that config.
+fn navigate_to_alias_config(
+    req: &mut AssetRequirer,
+    chunk_name: &str,
+    alias: &str,
+) -> Result<String, ResolveError> {
+    let result = req.reset(chunk_name);
+    nav_result(req, result)?;
+
+    loop {
+        if req.to_parent().is_err() {
+            return Err(ResolveError::UnknownAlias(alias.to_string()));
+        }
+
+        if !req.has_config() {
+            continue;
+        }
+
+        let contents = req
+            .config()
+            .map_err(|e| ResolveError::Other(format!("Failed to read config: {e}")))?;
+        let aliases = parse_luaurc_aliases(&contents)
+            .map_err(|e| ResolveError::Other(format!("Failed to parse config: {e}")))?;
+
+        if let Some(value) = aliases.get(alias) {
+            return Ok(value.clone());
+        }
+    }
+}
+
+fn navigate_to_alias(
+    req: &mut AssetRequirer,
+    chunk_name: &str,
+    alias: &str,
+    depth: usize,
+) -> Result<(), ResolveError> {
+    if depth > MAX_ALIAS_DEPTH {
+        return Err(ResolveError::Other(format!(
+            "alias @{alias} exceeds the maximum alias depth of {MAX_ALIAS_DEPTH}"
+        )));
+    }
+
+    let value = navigate_to_alias_config(req, chunk_name, alias)?;
+
+    if let Some(rest) = value.strip_prefix('@') {
+        let (next_alias, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        navigate_to_alias(req, chunk_name, &next_alias.to_lowercase(), depth + 1)?;
+        return navigate_through(req, rest);
+    }
+
+    if value.starts_with("./") || value.starts_with("../") || value == "." || value == ".." {
+        // Relative aliases are resolved from the directory containing the config
+        return navigate_through(req, &value);
+    }
+
+    let result = req.jump_to_alias(&value);
+    nav_result(req, result)
+}
+
+/// Resolves `path` as if it were required from the chunk named `chunk_name`, returning the
+/// absolute VFS path of the module without loading it
+pub(crate) fn resolve_require(
+    req: &mut AssetRequirer,
+    chunk_name: &str,
+    path: &str,
+) -> Result<String, ResolveError> {
+    if let Some(aliased) = path.strip_prefix('@') {
+        let (alias, rest) = aliased.split_once('/').unwrap_or((aliased, ""));
+        let alias = alias.to_lowercase();
+        if alias == "self" {
+            let result = req.reset(chunk_name);
+            nav_result(req, result)?;
+        } else {
+            navigate_to_alias(req, chunk_name, &alias, 0)?;
+        }
+        navigate_through(req, rest)?;
+    } else if path.starts_with("./") || path.starts_with("../") {
+        let result = req.reset(chunk_name);
+        nav_result(req, result)?;
+        let result = req.to_parent();
+        nav_result(req, result)?;
+        navigate_through(req, path)?;
+    } else {
+        return Err(ResolveError::InvalidPath(path.to_string()));
+    }
+
+    if !req.has_module() {
+        return Err(ResolveError::NotFound {
+            probes: req.probes().to_vec(),
+        });
+    }
+
+    Ok(req.absolute_path().to_string())
+}
@@ -0,0 +1,173 @@
+// Filesystems whose contents are vouched for by an ed25519-signed manifest
+use super::lockfile::Lockfile;
+use super::readonly::read_only;
+use ed25519_dalek::{Signature, VerifyingKey};
+use std::collections::BTreeSet;
+use std::io::{Cursor, Read};
+use std::time::SystemTime;
+use vfs::error::VfsErrorKind;
+use vfs::path::{SeekAndRead, SeekAndWrite, VfsMetadata};
+use vfs::{FileSystem, VfsError, VfsResult};
+
+/// A read-only filesystem exposing only the files listed in a signed manifest
+///
+/// The manifest is a [`Lockfile`] serialized with [`Lockfile::to_json`], signed with ed25519 over
+/// its exact bytes. The signature and the contents of every listed file are checked when the
+/// filesystem is created, so it cannot be created over a tampered tree. Afterwards files missing
+/// from the manifest are reported as not found, and a listed file is checked again each time it
+/// is opened, so no unsigned or tampered module ever reaches
+/// [`AssetRequirer`](crate::AssetRequirer).
+#[derive(Debug)]
+pub struct SignedManifestFS {
+    inner: Box<dyn FileSystem>,
+    manifest: Lockfile,
+    dirs: BTreeSet<String>,
+}
+
+impl SignedManifestFS {
+    /// Wraps `fs` after verifying `signature` over `manifest` with the ed25519 `public_key`, and
+    /// every file listed in `manifest` against the contents of `fs`
+    pub fn new<T: FileSystem>(
+        fs: T,
+        manifest: &[u8],
+        signature: &[u8],
+        public_key: &[u8; 32],
+    ) -> Result<Self, crate::Error> {
+        let key = VerifyingKey::from_bytes(public_key)?;
+        let signature = Signature::from_slice(signature)?;
+        key.verify_strict(manifest, &signature)
+            .map_err(|e| format!("manifest signature is invalid: {e}"))?;
+
+        let manifest = Lockfile::from_json(manifest)?;
+        let mut dirs = BTreeSet::from([String::new()]);
+        for path in manifest.files.keys() {
+            if !path.starts_with('/') || path.split('/').skip(1).any(|c| c.is_empty() || c == "..")
+            {
+                return Err(format!("invalid path {path:?} in manifest").into());
+            }
+
+            let mut dir = path.as_str();
+            while let Some((parent, _)) = dir.rsplit_once('/') {
+                dirs.insert(parent.to_string());
+                dir = parent;
+            }
+        }
+
+        for path in manifest.files.keys() {
+            let mut contents = Vec::new();
+            fs.open_file(path)
+                .and_then(|mut file| Ok(file.read_to_end(&mut contents)?))
+                .map_err(|e| format!("cannot read {path} listed in manifest: {e}"))?;
+            manifest.verify(path, &contents)?;
+        }
+
+        Ok(Self {
+            inner: Box::new(fs),
+            manifest,
+            dirs,
+        })
+    }
+
+    fn is_listed_file(&self, path: &str) -> bool {
+        self.manifest.files.contains_key(path)
+    }
+
+    fn is_listed_dir(&self, path: &str) -> bool {
+        // The root may be addressed as either `` or `/`
+        self.dirs.contains(path.trim_end_matches('/'))
+    }
+
+    fn is_listed(&self, path: &str) -> bool {
+        self.is_listed_file(path) || self.is_listed_dir(path)
+    }
+}
+
+fn not_found<T>() -> VfsResult<T> {
+    Err(VfsError::from(VfsErrorKind::FileNotFound))
+}
+
+impl FileSystem for SignedManifestFS {
+    fn read_dir(&self, path: &str) -> VfsResult<Box<dyn Iterator<Item = String> + Send>> {
+        if !self.is_listed_dir(path) {
+            return not_found();
+        }
+
+        let dir = path.trim_end_matches('/');
+        let entries = self
+            .inner
+            .read_dir(path)?
+            .filter(|entry| self.is_listed(&format!("{dir}/{entry}")))
+            .collect::<Vec<_>>();
+        Ok(Box::new(entries.into_iter()))
+    }
+
+    fn create_dir(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn open_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndRead + Send>> {
+        if !self.is_listed_file(path) {
+            return not_found();
+        }
+
+        // Serve the verified bytes so the file cannot change between the check and the read
+        let mut contents = Vec::new();
+        self.inner.open_file(path)?.read_to_end(&mut contents)?;
+        self.manifest
+            .verify(path, &contents)
+            .map_err(|e| VfsError::from(VfsErrorKind::Other(e.to_string())))?;
+        Ok(Box::new(Cursor::new(contents)))
+    }
+
+    fn create_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        read_only(path)
+    }
+
+    fn append_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        read_only(path)
+    }
+
+    fn metadata(&self, path: &str) -> VfsResult<VfsMetadata> {
+        if !self.is_listed(path) {
+            return not_found();
+        }
+
+        self.inner.metadata(path)
+    }
+
+    fn set_creation_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn set_modification_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn set_access_time(&self, path: &str, _time: SystemTime) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn exists(&self, path: &str) -> VfsResult<bool> {
+        Ok(self.is_listed(path) && self.inner.exists(path)?)
+    }
+
+    fn remove_file(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn remove_dir(&self, path: &str) -> VfsResult<()> {
+        read_only(path)
+    }
+
+    fn copy_file(&self, _src: &str, dest: &str) -> VfsResult<()> {
+        read_only(dest)
+    }
+
+    fn move_file(&self, src: &str, _dest: &str) -> VfsResult<()> {
+        read_only(src)
+    }
+
+    fn move_dir(&self, src: &str, _dest: &str) -> VfsResult<()> {
+        read_only(src)
+    }
+}
@@ -0,0 +1,170 @@
+// Serializable snapshots of a whole filesystem tree
+use super::fswrapper::FilesystemWrapper;
+use sha2::{Digest, Sha256};
+use vfs::FileSystem;
+
+const MAGIC: &[u8; 8] = b"MLRQSNAP";
+
+/// Current version of the snapshot format, as described on [`save_snapshot`]
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const KIND_DIRECTORY: u8 = 0;
+const KIND_FILE: u8 = 1;
+const FLAG_SHA256: u8 = 1;
+
+/// Serializes every file and directory in `fs`, with SHA-256 hashes of file contents
+///
+/// # Format
+///
+/// Version 1 of the format is laid out as follows, with all integers little-endian:
+///
+/// | Field     | Size          | Contents               |
+/// |-----------|---------------|------------------------|
+/// | `magic`   | 8 bytes       | `b"MLRQSNAP"`          |
+/// | `version` | `u32`         | [`SNAPSHOT_VERSION`]   |
+/// | `count`   | `u32`         | Number of entries      |
+/// | `entries` | `count` times | Entries sorted by path |
+///
+/// Each entry is:
+///
+/// | Field      | Size                 | Contents                                         |
+/// |------------|----------------------|--------------------------------------------------|
+/// | `kind`     | `u8`                 | `0` for a directory, `1` for a file              |
+/// | `flags`    | `u8`                 | Bit 0: a SHA-256 hash follows the contents       |
+/// | `path`     | `u32` length + bytes | Absolute, `/`-separated UTF-8 path               |
+/// | `contents` | `u64` length + bytes | Raw file contents (files only)                   |
+/// | `sha256`   | 32 bytes             | Hash of the contents (files with bit 0 set only) |
+///
+/// Parent directories always precede their children.
+pub fn save_snapshot(fs: &FilesystemWrapper) -> Result<Vec<u8>, crate::Error> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![String::new()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs.read_dir(&dir)? {
+            let path = format!("{dir}/{entry}");
+            if fs.is_dir(path.clone())? {
+                dirs.push(path.clone());
+                entries.push((path, None));
+            } else {
+                let contents = fs.get_file(path.clone())?;
+                entries.push((path, Some(contents)));
+            }
+        }
+    }
+
+    // Sorting by path also places directories before their children
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    out.extend_from_slice(&u32::try_from(entries.len())?.to_le_bytes());
+    for (path, contents) in entries {
+        match contents {
+            None => {
+                out.extend_from_slice(&[KIND_DIRECTORY, 0]);
+                write_path(&mut out, &path)?;
+            }
+            Some(contents) => {
+                out.extend_from_slice(&[KIND_FILE, FLAG_SHA256]);
+                write_path(&mut out, &path)?;
+                out.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+                out.extend_from_slice(&contents);
+                out.extend_from_slice(&Sha256::digest(&contents));
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn write_path(out: &mut Vec<u8>, path: &str) -> Result<(), crate::Error> {
+    out.extend_from_slice(&u32::try_from(path.len())?.to_le_bytes());
+    out.extend_from_slice(path.as_bytes());
+    Ok(())
+}
+
+/// Loads a snapshot created by [`save_snapshot`] into a virtual filesystem
+pub fn load_snapshot(bytes: &[u8]) -> Result<FilesystemWrapper, crate::Error> {
+    Ok(FilesystemWrapper::new(load_memory_snapshot(bytes)?))
+}
+
+/// Loads a snapshot created by [`save_snapshot`] into a concrete MemoryFS
+///
+/// Fails if the snapshot is malformed, has an unsupported version or a file does not match its
+/// hash.
+pub fn load_memory_snapshot(bytes: &[u8]) -> Result<vfs::MemoryFS, crate::Error> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err("not a filesystem snapshot".into());
+    }
+
+    let version = reader.u32()?;
+    if version != SNAPSHOT_VERSION {
+        return Err(format!("unsupported snapshot version {version}").into());
+    }
+
+    let fs = vfs::MemoryFS::new();
+    for _ in 0..reader.u32()? {
+        let kind = reader.take(1)?[0];
+        let flags = reader.take(1)?[0];
+        let len = reader.u32()? as usize;
+        let path = std::str::from_utf8(reader.take(len)?)?;
+        if !path.starts_with('/') || path.split('/').skip(1).any(|c| c.is_empty() || c == "..") {
+            return Err(format!("invalid path {path:?} in snapshot").into());
+        }
+
+        match kind {
+            KIND_DIRECTORY => fs
+                .create_dir(path)
+                .map_err(|e| format!("Failed to create directory {path}: {e}"))?,
+            KIND_FILE => {
+                let len = usize::try_from(reader.u64()?)?;
+                let contents = reader.take(len)?;
+                if flags & FLAG_SHA256 != 0
+                    && Sha256::digest(contents).as_slice() != reader.take(32)?
+                {
+                    return Err(format!("{path} does not match its hash").into());
+                }
+
+                fs.create_file(path)
+                    .map_err(|e| format!("Failed to create file {path}: {e}"))?
+                    .write_all(contents)
+                    .map_err(|e| format!("Failed to write to {path}: {e}"))?;
+            }
+            _ => return Err(format!("unknown entry kind {kind} for {path}").into()),
+        }
+    }
+
+    if reader.pos != bytes.len() {
+        return Err("trailing data after snapshot entries".into());
+    }
+
+    Ok(fs)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], crate::Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or("snapshot is truncated")?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, crate::Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, crate::Error> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
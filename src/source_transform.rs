@@ -0,0 +1,16 @@
+/// Rewrites module source before it is compiled
+///
+/// Transforms receive the absolute VFS path of the module and its current source, and are
+/// applied in the order they were added to the [`AssetRequirer`](crate::AssetRequirer)
+pub trait SourceTransform {
+    fn transform(&self, path: &str, source: Vec<u8>) -> Result<Vec<u8>, crate::Error>;
+}
+
+impl<F> SourceTransform for F
+where
+    F: Fn(&str, Vec<u8>) -> Result<Vec<u8>, crate::Error>,
+{
+    fn transform(&self, path: &str, source: Vec<u8>) -> Result<Vec<u8>, crate::Error> {
+        self(path, source)
+    }
+}
@@ -0,0 +1,67 @@
+// Canonicalisation of physical paths through symlinks
+use std::path::{Path, PathBuf};
+
+/// What to do with modules whose real path, after following symlinks, lies outside the
+/// filesystem root
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SymlinkEscapePolicy {
+    /// Load the module, keyed by its canonical host path
+    Follow,
+    /// Treat the module as missing
+    #[default]
+    Reject,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SymlinkResolver {
+    root: PathBuf,
+    pub(crate) policy: SymlinkEscapePolicy,
+}
+
+pub(crate) enum CanonicalPath {
+    /// The canonical path lies inside the root, as an absolute VFS path
+    Inside(String),
+    /// The canonical path escapes the root, as a host path
+    Outside(PathBuf),
+}
+
+impl SymlinkResolver {
+    pub(crate) fn new(root: PathBuf, policy: SymlinkEscapePolicy) -> Self {
+        let root = root.canonicalize().unwrap_or(root);
+        Self { root, policy }
+    }
+
+    /// Follows symlinks in the absolute VFS path `path`
+    pub(crate) fn canonicalize(&self, path: &str) -> std::io::Result<CanonicalPath> {
+        let canonical = self
+            .root
+            .join(path.trim_start_matches('/'))
+            .canonicalize()?;
+
+        match canonical.strip_prefix(&self.root) {
+            Ok(relative) => Ok(CanonicalPath::Inside(vfs_path(relative))),
+            Err(_) => Ok(CanonicalPath::Outside(canonical)),
+        }
+    }
+}
+
+fn vfs_path(relative: &Path) -> String {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    format!("/{relative}")
+}
+
+/// Error raised when a require would leave the root configured with
+/// [`AssetRequirer::with_root_jail`](crate::AssetRequirer::with_root_jail)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RootEscapeError {
+    /// The VFS path that escapes the root
+    pub path: String,
+}
+
+impl std::fmt::Display for RootEscapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} escapes the filesystem root", self.path)
+    }
+}
+
+impl std::error::Error for RootEscapeError {}
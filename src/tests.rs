@@ -218,3 +218,1636 @@ resolved = true
 
     assert!(l);
 }
+
+#[test]
+fn test_resolve_function() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        r#"
+local found = resolve("./lib/util")
+assert(found == "/lib/util.luau", found)
+local aliased = resolve("@lib/util")
+assert(aliased == "/lib/util.luau", aliased)
+local missing, probes = resolve("./lib/missing")
+assert(missing == nil)
+assert(table.find(probes, "/lib/missing.luau"))
+return true
+"#
+        .to_string(),
+    );
+    tree.insert("lib/util.luau".to_string(), "return 1".to_string());
+    tree.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "./lib".to_string(),
+        }),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let mut c = AssetRequirer::new(fs.clone(), "resolve".to_string(), lua.globals());
+
+    assert_eq!(
+        c.resolve("/main", "./lib/util").expect("Failed to resolve"),
+        "/lib/util.luau"
+    );
+    assert!(matches!(
+        c.resolve("/main", "lib/util"),
+        Err(crate::ResolveError::InvalidPath(_))
+    ));
+
+    lua.globals()
+        .set("resolve", c.create_resolve_function(&lua).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let ok: bool = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert!(ok);
+}
+
+#[test]
+fn test_asset_loaders() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        r#"
+local config = require("./config.json")
+assert(config.name == "test")
+assert(config.values[2] == 2)
+local probed = require("./config")
+assert(probed == config)
+assert(require("./notes.txt") == "hello")
+return true
+"#
+        .to_string(),
+    );
+    tree.insert(
+        "config.json".to_string(),
+        r#"{"name": "test", "values": [1, 2, 3]}"#.to_string(),
+    );
+    tree.insert("notes.txt".to_string(), "hello".to_string());
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "assets".to_string(), lua.globals())
+        .with_asset_loader("json", crate::JsonLoader)
+        .with_asset_loader("txt", crate::TextLoader)
+        .with_asset_extension_probing(true);
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let ok: bool = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert!(ok);
+}
+
+#[test]
+fn test_source_transforms() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('./lib')".to_string(),
+    );
+    tree.insert(
+        "lib.luau".to_string(),
+        "--!pragma custom\nreturn VALUE".to_string(),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "transform".to_string(), lua.globals())
+        .with_source_transform(|_path: &str, source: Vec<u8>| {
+            let source = String::from_utf8(source)?;
+            Ok(source
+                .strip_prefix("--!pragma custom\n")
+                .unwrap_or(&source)
+                .as_bytes()
+                .to_vec())
+        })
+        .with_source_transform(|path: &str, source: Vec<u8>| {
+            let source = String::from_utf8(source)?;
+            Ok(format!("-- {path}\n{}", source.replace("VALUE", "42")).into_bytes())
+        });
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let v: i32 = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert_eq!(v, 42);
+}
+
+#[test]
+fn test_programmatic_aliases() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        r#"
+assert(require("@lib/util") == "vendored")
+assert(require("@std/math").double(2) == 4)
+assert(require("@shared") == "from luaurc")
+return true
+"#
+        .to_string(),
+    );
+    tree.insert(
+        "vendor/lib/util.luau".to_string(),
+        "return 'vendored'".to_string(),
+    );
+    tree.insert(
+        "shared/init.luau".to_string(),
+        "return 'from luaurc'".to_string(),
+    );
+    tree.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "shared".to_string() => "./shared".to_string(),
+        }),
+    );
+
+    let mut std_tree = std::collections::HashMap::new();
+    std_tree.insert(
+        "math.luau".to_string(),
+        "local helpers = require('./helpers')\nreturn { double = helpers.double }".to_string(),
+    );
+    std_tree.insert(
+        "helpers.luau".to_string(),
+        "return { double = function(x) return x * 2 end }".to_string(),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let std_fs = super::memoryvfs::create_vfs_from_map(&std_tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "aliases".to_string(), lua.globals())
+        .with_alias("lib", "/vendor/lib")
+        .with_alias("shared", "/vendor/lib")
+        .with_alias_fs("std", std_fs);
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let ok: bool = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert!(ok);
+
+    // Host aliases survive configs that only Luau's parser accepts
+    tree.insert(
+        ".luaurc".to_string(),
+        "{\n  -- local overrides\n  \"aliases\": {\n    \"shared\": \"./shared\",\n    \"bare\": \"vendor/lib\",\n  },\n}"
+            .to_string(),
+    );
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let std_fs = super::memoryvfs::create_vfs_from_map(&std_tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "aliases".to_string(), lua.globals())
+        .with_alias("lib", "/vendor/lib")
+        .with_alias("shared", "/vendor/lib")
+        .with_alias_fs("std", std_fs);
+    let mut resolver = c.clone();
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let ok: bool = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert!(ok);
+
+    // Alias targets that are not absolute VFS paths are rejected
+    assert!(matches!(
+        resolver.resolve("/main", "@bare/util"),
+        Err(crate::ResolveError::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_alias_hooks() {
+    struct Hooks;
+
+    impl crate::AliasHooks for Hooks {
+        fn names(&self) -> Vec<String> {
+            vec!["std".to_string(), "plugin".to_string()]
+        }
+
+        fn override_alias(&self, alias: &str) -> Option<String> {
+            (alias == "std").then(|| "/pinned/std".to_string())
+        }
+
+        fn fallback_alias(&self, alias: &str) -> Option<String> {
+            (alias == "plugin").then(|| "/plugins/default".to_string())
+        }
+    }
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "project/main.luau".to_string(),
+        r#"
+assert(require("@std/version") == "pinned")
+assert(require("@plugin/name") == "default")
+return true
+"#
+        .to_string(),
+    );
+    tree.insert(
+        "project/.luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "std".to_string() => "./user-std".to_string(),
+        }),
+    );
+    tree.insert(
+        "project/user-std/version.luau".to_string(),
+        "return 'hijacked'".to_string(),
+    );
+    tree.insert(
+        "pinned/std/version.luau".to_string(),
+        "return 'pinned'".to_string(),
+    );
+    tree.insert(
+        "plugins/default/name.luau".to_string(),
+        "return 'default'".to_string(),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c =
+        AssetRequirer::new(fs.clone(), "hooks".to_string(), lua.globals()).with_alias_hooks(Hooks);
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/project/main.luau".to_string()).unwrap();
+    let ok: bool = lua
+        .load(main)
+        .set_name("/project/main")
+        .call(())
+        .expect("Failed to run main");
+    assert!(ok);
+
+    // Overrides still apply to configs serde_json would reject, and configs that cannot be
+    // parsed at all fail instead of being served without them
+    let run = |luaurc: &str| {
+        let mut tree = tree.clone();
+        tree.insert("project/.luaurc".to_string(), luaurc.to_string());
+        let lua = mluau::Lua::new();
+        let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+        let c = AssetRequirer::new(fs, "hooks".to_string(), lua.globals()).with_alias_hooks(Hooks);
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        lua.load("return require('@std/version')")
+            .set_name("/project/main")
+            .call::<String>(())
+    };
+
+    assert_eq!(
+        run("{\"aliases\": {\"std\": \"./user-std\",}}").unwrap(),
+        "pinned"
+    );
+    assert_eq!(
+        run("-- pinned by the host\n{'aliases': {'STD': './user-std'}}").unwrap(),
+        "pinned"
+    );
+    assert!(run("{\"aliases\": {\"std\": \"./user-std\"").is_err());
+}
+
+#[test]
+fn test_config_luau() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('@lib/util')".to_string(),
+    );
+    tree.insert("lib/util.luau".to_string(), "return 5".to_string());
+    tree.insert(
+        ".config.luau".to_string(),
+        "return { luau = { aliases = { lib = './lib' } } }".to_string(),
+    );
+    tree.insert(
+        "conflict/main.luau".to_string(),
+        "return require('@lib/util')".to_string(),
+    );
+    tree.insert(
+        "conflict/.config.luau".to_string(),
+        "return { luau = { aliases = { lib = '../lib' } } }".to_string(),
+    );
+    tree.insert(
+        "conflict/.luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "../lib".to_string(),
+        }),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "config_luau".to_string(), lua.globals());
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let v: i32 = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert_eq!(v, 5);
+
+    let conflict = fs.get_file("/conflict/main.luau".to_string()).unwrap();
+    assert!(
+        lua.load(conflict)
+            .set_name("/conflict/main")
+            .call::<i32>(())
+            .is_err(),
+        "Conflicting configs should fail"
+    );
+
+    // Memory bombs, self-referencing tables and deeply nested tables are rejected
+    for config in [
+        "return { luau = { aliases = { lib = ('x'):rep(2^31) } } }",
+        "local t = {} t.x = t return { luau = { aliases = t } }",
+        "local t = {} for _ = 1, 10000 do t = { t } end return { luau = { aliases = t } }",
+    ] {
+        let mut bomb_tree = std::collections::HashMap::new();
+        bomb_tree.insert(
+            "main.luau".to_string(),
+            "return require('@lib/util')".to_string(),
+        );
+        bomb_tree.insert("lib/util.luau".to_string(), "return 5".to_string());
+        bomb_tree.insert(".config.luau".to_string(), config.to_string());
+        let bomb_fs =
+            super::memoryvfs::create_vfs_from_map(&bomb_tree).expect("Failed to make vfs");
+        let lua = mluau::Lua::new();
+        let c = AssetRequirer::new(bomb_fs, "config_luau".to_string(), lua.globals());
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        assert!(
+            lua.load("return require('@lib/util')")
+                .set_name("/main")
+                .call::<i32>(())
+                .is_err(),
+            "{config} should fail"
+        );
+    }
+}
+
+#[test]
+fn test_luaurc_round_trip() {
+    use super::luaurc::{Node, parse_luaurc, write_luaurc};
+
+    let write = |node: &Node| {
+        let mut out = Vec::new();
+        write_luaurc(node, &mut out);
+        out
+    };
+
+    for value in [
+        "./lib",
+        "it's",
+        "say \"hi\"",
+        "both \" and '",
+        "C:\\lib\\",
+        "two\nlines",
+    ] {
+        let written = write(&Node::String(value.to_string()));
+        let parsed = parse_luaurc(&written).unwrap_or_else(|e| panic!("{value:?}: {e}"));
+        assert_eq!(write(&parsed), written, "{value:?}");
+        if !value.contains(['"', '\n']) && !value.ends_with('\\') {
+            assert!(matches!(parsed, Node::String(read) if read == value));
+        }
+    }
+
+    let deep = parse_luaurc("[".repeat(300_000).as_bytes());
+    assert!(deep.is_err_and(|e| e.message.contains("nested") && e.column == 129));
+}
+
+#[test]
+fn test_luaurc_lint() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("lib/util.luau".to_string(), "return 1".to_string());
+    tree.insert(
+        ".luaurc".to_string(),
+        "{\n  \"aliases\": {\n    \"lib\": \"./lib\",\n    \"Lib\": \"./lib\",\n    \"bad name\": \"./lib\",\n    \"missing\": \"./nope\",\n    \"lib\": 5\n  }\n}"
+            .to_string(),
+    );
+    tree.insert("broken/.luaurc".to_string(), "{ \"aliases\": ".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+
+    let diagnostics = crate::lint_luaurc(&fs, "/.luaurc").expect("Failed to lint");
+    let kinds = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.line))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            (crate::LuaurcDiagnosticKind::CaseCollidingAlias, 4),
+            (crate::LuaurcDiagnosticKind::InvalidAliasName, 5),
+            (crate::LuaurcDiagnosticKind::MissingAliasTarget, 6),
+            (crate::LuaurcDiagnosticKind::DuplicateAlias, 7),
+            (crate::LuaurcDiagnosticKind::InvalidAliasTarget, 7),
+        ]
+    );
+    assert_eq!(diagnostics[0].column, 5);
+
+    let all = crate::lint_all_luaurc(&fs).expect("Failed to lint");
+    assert!(
+        all.iter()
+            .any(|d| d.path == "/broken/.luaurc"
+                && d.kind == crate::LuaurcDiagnosticKind::InvalidJson)
+    );
+
+    let mut strict_tree = std::collections::HashMap::new();
+    strict_tree.insert("main.luau".to_string(), "return 1".to_string());
+    strict_tree.insert("lib/util.luau".to_string(), "return 1".to_string());
+    strict_tree.insert(
+        ".luaurc".to_string(),
+        r#"{"aliases": {"lib": "./lib", "LIB": "./lib"}}"#.to_string(),
+    );
+    let strict_fs =
+        super::memoryvfs::create_vfs_from_map(&strict_tree).expect("Failed to make vfs");
+
+    let lua = mluau::Lua::new();
+    let mut lenient = AssetRequirer::new(strict_fs.clone(), "lenient".to_string(), lua.globals());
+    assert!(lenient.resolve("/main", "@lib/util").is_ok());
+
+    let mut strict =
+        AssetRequirer::new(strict_fs, "strict".to_string(), lua.globals()).with_strict_config(true);
+    assert!(matches!(
+        strict.resolve("/main", "@lib/util"),
+        Err(crate::ResolveError::Other(_))
+    ));
+
+    // Luau accepts comments and trailing commas in configs
+    let mut luau_tree = std::collections::HashMap::new();
+    luau_tree.insert("main.luau".to_string(), "return 1".to_string());
+    luau_tree.insert("lib/util.luau".to_string(), "return 1".to_string());
+    luau_tree.insert(
+        ".luaurc".to_string(),
+        "{\n  -- vendored helpers\n  \"aliases\": {\n    \"lib\": \"./lib\",\n  },\n}".to_string(),
+    );
+    let luau_fs = super::memoryvfs::create_vfs_from_map(&luau_tree).expect("Failed to make vfs");
+    assert_eq!(crate::lint_luaurc(&luau_fs, "/.luaurc").unwrap(), vec![]);
+
+    let mut strict =
+        AssetRequirer::new(luau_fs, "strict".to_string(), lua.globals()).with_strict_config(true);
+    assert_eq!(
+        strict.resolve("/main", "@lib/util"),
+        Ok("/lib/util.luau".to_string())
+    );
+}
+
+#[test]
+fn test_virtual_config() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "embedded/lib/init.luau".to_string(),
+        "return require('@self/inner')".to_string(),
+    );
+    tree.insert(
+        "embedded/lib/inner.luau".to_string(),
+        "return require('@helpers/double')(21)".to_string(),
+    );
+    tree.insert(
+        "embedded/lib/helpers/double.luau".to_string(),
+        "return function(x) return x * 2 end".to_string(),
+    );
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('./embedded/lib')".to_string(),
+    );
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "virtual".to_string(), lua.globals())
+        .with_virtual_config(
+            "/embedded/lib",
+            create_luaurc_with_aliases(indexmap::indexmap! {
+                "helpers".to_string() => "./helpers".to_string(),
+            }),
+        );
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let v: i32 = lua
+        .load(main)
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert_eq!(v, 42);
+}
+
+#[test]
+fn test_case_sensitivity() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('./Lib/Utils')".to_string(),
+    );
+    tree.insert("lib/utils.luau".to_string(), "return 7".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+
+    let lua = mluau::Lua::new();
+    let strict = AssetRequirer::new(fs.clone(), "sensitive".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(strict).unwrap())
+        .unwrap();
+    assert!(
+        lua.load(main.clone())
+            .set_name("/main")
+            .call::<i32>(())
+            .is_err()
+    );
+
+    let lua = mluau::Lua::new();
+    let insensitive = AssetRequirer::new(fs.clone(), "insensitive".to_string(), lua.globals())
+        .with_case_sensitivity(crate::CaseSensitivity::Insensitive);
+    lua.globals()
+        .set("require", lua.create_require_function(insensitive).unwrap())
+        .unwrap();
+    let v: i32 = lua
+        .load(main.clone())
+        .set_name("/main")
+        .call(())
+        .expect("Failed to run main");
+    assert_eq!(v, 7);
+
+    let lua = mluau::Lua::new();
+    let mismatches = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let recorded = mismatches.clone();
+    let mut warn = AssetRequirer::new(fs, "warn".to_string(), lua.globals())
+        .with_case_sensitivity(crate::CaseSensitivity::WarnOnMismatch)
+        .with_case_mismatch_handler(move |requested, actual| {
+            recorded
+                .borrow_mut()
+                .push((requested.to_string(), actual.to_string()))
+        });
+    assert!(warn.resolve("/main", "./Lib/Utils").is_err());
+    assert!(
+        mismatches
+            .borrow()
+            .iter()
+            .any(|(requested, actual)| requested == "/Lib" && actual == "/lib")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_resolution() {
+    let base = std::env::temp_dir().join(format!("mluau-require-symlinks-{}", std::process::id()));
+    let root = base.join("root");
+    let outside = base.join("outside");
+    std::fs::create_dir_all(root.join("real")).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(root.join("real/mod.luau"), "return {}").unwrap();
+    std::fs::write(outside.join("secret.luau"), "return 'secret'").unwrap();
+    std::os::unix::fs::symlink(root.join("real"), root.join("linked")).unwrap();
+    std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+    std::fs::write(
+        root.join("main.luau"),
+        r#"
+assert(require("./real/mod") == require("./linked/mod"))
+assert(not pcall(require, "./escape/secret"))
+return true
+"#,
+    )
+    .unwrap();
+
+    let lua = mluau::Lua::new();
+    let fs = FilesystemWrapper::new(vfs::PhysicalFS::new(&root));
+    let c = AssetRequirer::new(fs.clone(), "symlinks".to_string(), lua.globals())
+        .with_symlink_resolution(&root, crate::SymlinkEscapePolicy::Reject);
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let result = lua.load(main).set_name("/main").call::<bool>(());
+    std::fs::remove_dir_all(&base).unwrap();
+    assert!(result.expect("Failed to run main"));
+}
+
+#[test]
+fn test_cache_key_strategies() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('./a/lib') == require('./b/lib')".to_string(),
+    );
+    tree.insert("a/lib.luau".to_string(), "return {}".to_string());
+    tree.insert("b/lib.luau".to_string(), "return {}".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+
+    let run = |strategy: crate::CacheKeyStrategy| -> bool {
+        let lua = mluau::Lua::new();
+        let c = AssetRequirer::new(fs.clone(), "keys".to_string(), lua.globals())
+            .with_cache_key_strategy(strategy);
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        lua.load(main.clone())
+            .set_name("/main")
+            .call(())
+            .expect("Failed to run main")
+    };
+
+    assert!(!run(crate::CacheKeyStrategy::Path));
+    assert!(run(crate::CacheKeyStrategy::ContentHash));
+    assert!(!run(crate::CacheKeyStrategy::PathAndVersion(
+        "1.0".to_string()
+    )));
+    assert!(run(crate::CacheKeyStrategy::Custom(std::rc::Rc::new(
+        |prefix: &str, _path: &str| format!("{prefix}@shared")
+    ))));
+}
+
+#[test]
+fn test_module_cache() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("main.luau".to_string(), "return 0".to_string());
+    tree.insert("a.luau".to_string(), "return {}".to_string());
+    tree.insert("b.luau".to_string(), "return {}".to_string());
+    tree.insert("c.luau".to_string(), "return {}".to_string());
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "cache".to_string(), lua.globals());
+    let cache = c.module_cache();
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let require = |path: &str| -> mluau::Table {
+        lua.load(format!("return require('{path}')"))
+            .set_name("/main")
+            .call(())
+            .expect("Failed to require")
+    };
+
+    let a = require("./a");
+    require("./b");
+
+    let loaded = cache.loaded(&lua).unwrap();
+    assert_eq!(
+        loaded.iter().map(|m| m.path.as_str()).collect::<Vec<_>>(),
+        vec!["/a.luau", "/b.luau"]
+    );
+    assert_eq!(loaded[0].cache_key, "cache@/a.luau");
+
+    assert!(cache.unload(&lua, "cache@/a.luau").unwrap());
+    assert_ne!(require("./a"), a);
+
+    // Clearing sweeps every requirer sharing the prefix, and only those
+    let other = AssetRequirer::new(fs.clone(), "cache".to_string(), lua.globals());
+    let other_fn = lua.create_require_function(other).unwrap();
+    let unrelated = AssetRequirer::new(fs.clone(), "cache2".to_string(), lua.globals());
+    let unrelated_fn = lua.create_require_function(unrelated).unwrap();
+    let extended = AssetRequirer::new(fs, "cache@x".to_string(), lua.globals());
+    let extended_fn = lua.create_require_function(extended).unwrap();
+    for require_fn in [other_fn, unrelated_fn, extended_fn] {
+        lua.load("local require = ...; return require('./c')")
+            .set_name("/main")
+            .call::<mluau::Table>(require_fn)
+            .expect("Failed to require");
+    }
+
+    assert_eq!(cache.clear(&lua, "cache").unwrap(), 3);
+    assert!(cache.loaded(&lua).unwrap().is_empty());
+    let modules = lua
+        .named_registry_value::<mluau::Table>("_MODULES")
+        .unwrap();
+    assert!(modules.contains_key("cache2@/c.luau").unwrap());
+    assert!(modules.contains_key("cache@x@/c.luau").unwrap());
+    assert!(!modules.contains_key("cache@/c.luau").unwrap());
+}
+
+#[test]
+fn test_require_events() {
+    use crate::RequireEvent;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("main.luau".to_string(), "return 0".to_string());
+    tree.insert("ok.luau".to_string(), "return 1".to_string());
+    tree.insert("bad.luau".to_string(), "error('boom')".to_string());
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let recorded = events.clone();
+    let c = AssetRequirer::new(fs, "events".to_string(), lua.globals())
+        .with_event_handler(move |e: &RequireEvent| recorded.borrow_mut().push(e.clone()));
+
+    lua.globals()
+        .set("require", lua.create_require_function(c.clone()).unwrap())
+        .unwrap();
+
+    let require = |path: &str| {
+        lua.load(format!("return require('{path}')"))
+            .set_name("/main")
+            .call::<mluau::Value>(())
+    };
+
+    assert!(require("./ok").is_ok());
+    let names = events
+        .borrow()
+        .iter()
+        .map(|e| match e {
+            RequireEvent::ResolveStarted { .. } => "ResolveStarted",
+            RequireEvent::ResolveFinished { .. } => "ResolveFinished",
+            RequireEvent::LoadStarted { .. } => "LoadStarted",
+            RequireEvent::Compiled { .. } => "Compiled",
+            RequireEvent::Executed { .. } => "Executed",
+            _ => "Other",
+        })
+        .collect::<Vec<_>>();
+    for name in [
+        "ResolveStarted",
+        "ResolveFinished",
+        "LoadStarted",
+        "Compiled",
+        "Executed",
+    ] {
+        assert!(names.contains(&name), "missing {name} in {names:?}");
+    }
+    assert!(events.borrow().iter().any(|e| matches!(
+        e,
+        RequireEvent::Compiled { path, bytes: 8, .. } if path == "/ok.luau"
+    )));
+
+    events.borrow_mut().clear();
+    assert!(require("./missing").is_err());
+    assert!(events.borrow().iter().any(|e| matches!(
+        e,
+        RequireEvent::ResolveFailed { probes, .. } if probes.contains(&"/missing.luau".to_string())
+    )));
+
+    events.borrow_mut().clear();
+    assert!(require("./bad").is_err());
+    assert!(events.borrow().iter().any(|e| matches!(
+        e,
+        RequireEvent::ExecutionFailed { path, .. } if path == "/bad.luau"
+    )));
+
+    // Static analysis and resolve only navigate, so they report nothing
+    events.borrow_mut().clear();
+    let mut resolver = c.clone();
+    assert_eq!(
+        resolver.resolve("/main", "./ok"),
+        Ok("/ok.luau".to_string())
+    );
+    assert!(resolver.resolve("/main", "./missing").is_err());
+    c.require_graph().expect("Failed to build require graph");
+    c.bundle("/main.luau").expect("Failed to bundle");
+    assert!(events.borrow().is_empty(), "{:?}", events.borrow());
+}
+
+#[test]
+fn test_require_graph() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "./lib".to_string(),
+        }),
+    );
+    tree.insert(
+        "main.luau".to_string(),
+        r#"
+-- require("./commented")
+--[[ require("./also_commented") ]]
+local a = require("./a")
+local b = require "@lib/b"
+local s = "require('./in_string')"
+local x = t.require("./field")
+local m = require("./missing")
+return a .. require('./dup')
+"#
+        .to_string(),
+    );
+    tree.insert("a.luau".to_string(), "return require(`./b`)".to_string());
+    tree.insert("b.luau".to_string(), "return 0".to_string());
+    tree.insert("lib/b.luau".to_string(), "return 0".to_string());
+    tree.insert("dup.luau".to_string(), "return 0".to_string());
+    tree.insert("dup.lua".to_string(), "return 0".to_string());
+    tree.insert(
+        "escapes.luau".to_string(),
+        r#"
+local a = require("\x2e/\u{62}")
+local b = require('./\98')
+local c = require("./\z
+    b")
+local d = require("./\q")
+return a
+"#
+        .to_string(),
+    );
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let graph = crate::analyze_requires(&fs).expect("Failed to analyze requires");
+
+    assert_eq!(
+        graph.dependencies("/main.luau"),
+        vec!["/a.luau", "/lib/b.luau"]
+    );
+    assert_eq!(graph.dependencies("/a.luau"), vec!["/b.luau"]);
+    assert_eq!(
+        graph.modules["/escapes.luau"]
+            .iter()
+            .map(|r| r.path.as_str())
+            .collect::<Vec<_>>(),
+        vec!["./b", "./b", "./b"]
+    );
+
+    let unresolved = graph.unresolved();
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].0, "/main.luau");
+    assert_eq!(unresolved[0].1.path, "./missing");
+    assert_eq!((unresolved[0].1.line, unresolved[0].1.column), (8, 19));
+
+    let ambiguous = graph.ambiguous();
+    assert_eq!(ambiguous.len(), 1);
+    assert_eq!(ambiguous[0].1.path, "./dup");
+    assert!(!graph.is_ok());
+}
+
+#[test]
+fn test_bundler() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "src/.luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "util".to_string() => "./util".to_string(),
+        }),
+    );
+    tree.insert(
+        "src/main.luau".to_string(),
+        "local greet = require('@util/greet')\nreturn greet(require('@shared/config').name)"
+            .to_string(),
+    );
+    tree.insert(
+        "src/util/greet.luau".to_string(),
+        "return function(name) return `hello {name}` end".to_string(),
+    );
+    tree.insert(
+        "shared/config.luau".to_string(),
+        "return { name = 'world' }".to_string(),
+    );
+    tree.insert("unused.luau".to_string(), "return 0".to_string());
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c =
+        AssetRequirer::new(fs, "bundle".to_string(), lua.globals()).with_alias("shared", "/shared");
+    let bundle = c.bundle("/src/main.luau").expect("Failed to bundle");
+
+    assert_eq!(
+        bundle.files.keys().collect::<Vec<_>>(),
+        vec![
+            ".luaurc",
+            "shared/config.luau",
+            "src/.luaurc",
+            "src/main.luau",
+            "src/util/greet.luau"
+        ]
+    );
+
+    // Single file
+    let result: String = lua
+        .load(bundle.to_luau().expect("Failed to render bundle"))
+        .call(())
+        .expect("Failed to run bundle");
+    assert_eq!(result, "hello world");
+
+    // Bundles edited by hand are checked rather than trusted
+    let mut missing_source = bundle.clone();
+    missing_source.files.remove("src/util/greet.luau");
+    assert!(missing_source.to_luau().is_err());
+    let mut missing_target = bundle.clone();
+    missing_target.requires.remove("/src/util/greet.luau");
+    assert!(missing_target.to_luau().is_err());
+
+    // Snapshot, with the programmatic alias flattened into the root .luaurc
+    let tree = crate::Bundle::tree_from_json(&bundle.to_json()).expect("Failed to read bundle");
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs.clone(), "snapshot".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/src/main.luau".to_string()).unwrap();
+    let result: String = lua
+        .load(main)
+        .set_name("/src/main.luau")
+        .call(())
+        .expect("Failed to run snapshot");
+    assert_eq!(result, "hello world");
+
+    let mut broken = std::collections::HashMap::new();
+    broken.insert("main.luau".to_string(), "require('./missing')".to_string());
+    let fs = super::memoryvfs::create_vfs_from_map(&broken).expect("Failed to make vfs");
+    assert!(crate::bundle(&fs, "/main.luau").is_err());
+}
+
+#[test]
+fn test_snapshot() {
+    use std::io::Write;
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('./lib/a')".to_string(),
+    );
+    tree.insert("lib/a.luau".to_string(), "return 'a'".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    fs.create_dir("/empty").unwrap();
+    fs.create_file("/lib/data.bin")
+        .unwrap()
+        .write_all(&[0, 159, 146, 150, 255])
+        .unwrap();
+
+    let bytes = crate::save_snapshot(&fs).expect("Failed to save snapshot");
+    assert_eq!(
+        bytes,
+        crate::save_snapshot(&crate::load_snapshot(&bytes).unwrap()).unwrap()
+    );
+
+    let loaded = crate::load_snapshot(&bytes).expect("Failed to load snapshot");
+    assert!(loaded.is_dir("/empty".to_string()).unwrap());
+    assert_eq!(
+        loaded.get_file("/lib/data.bin".to_string()).unwrap(),
+        vec![0, 159, 146, 150, 255]
+    );
+
+    let lua = mluau::Lua::new();
+    let c = AssetRequirer::new(loaded.clone(), "snapshot".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = loaded.get_file("/main.luau".to_string()).unwrap();
+    let result: String = lua.load(main).set_name("/main").call(()).unwrap();
+    assert_eq!(result, "a");
+
+    // Corrupted contents fail hash verification
+    let mut corrupted = bytes.clone();
+    let offset = corrupted
+        .windows(b"return 'a'".len())
+        .position(|w| w == b"return 'a'")
+        .unwrap();
+    corrupted[offset + 8] = b'b';
+    assert!(crate::load_snapshot(&corrupted).is_err());
+
+    assert!(crate::load_snapshot(&bytes[..bytes.len() - 1]).is_err());
+    assert!(crate::load_snapshot(b"not a snapshot").is_err());
+}
+
+#[test]
+fn test_binary_memory_vfs() {
+    let bytecode = vec![0x1b, 0x4c, 0xff, 0x00, 0x80];
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("main.luau".to_string(), b"return 0".to_vec());
+    tree.insert("bin/module.luauc".to_string(), bytecode.clone());
+    let fs = super::memoryvfs::create_vfs_from_bytes_map(&tree).expect("Failed to make vfs");
+    assert_eq!(
+        fs.get_file("/bin/module.luauc".to_string()).unwrap(),
+        bytecode
+    );
+
+    let fs = crate::create_vfs_from_iter([
+        ("main.luau", &b"return require('./data')"[..]),
+        ("data.bin", &bytecode[..]),
+    ])
+    .expect("Failed to make vfs");
+    assert_eq!(fs.get_file("/data.bin".to_string()).unwrap(), bytecode);
+    assert_eq!(
+        fs.get_file("/main.luau".to_string()).unwrap(),
+        b"return require('./data')"
+    );
+
+    // Later entries overwrite earlier ones
+    let fs = crate::create_vfs_from_iter(vec![
+        ("a.luau".to_string(), "return 1".to_string()),
+        ("a.luau".to_string(), "return 2".to_string()),
+    ])
+    .expect("Failed to make vfs");
+    assert_eq!(fs.get_file("/a.luau".to_string()).unwrap(), b"return 2");
+}
+
+#[test]
+fn test_memory_tree_builder() {
+    use crate::{MemoryTreeBuilder, MemoryTreeError};
+
+    let fs = MemoryTreeBuilder::new()
+        .with_file("/main.luau", "return require('./lib/a')")
+        .with_file("./lib//a.luau", "return require('./b')")
+        .with_file("lib\\sub\\..\\b.luau", "return 'b'")
+        .with_dir("empty/nested")
+        .with_dir("lib")
+        .build_wrapper()
+        .expect("Failed to build tree");
+
+    assert!(fs.is_dir("/empty/nested".to_string()).unwrap());
+    assert!(fs.is_file("/lib/b.luau".to_string()).unwrap());
+    assert!(!fs.exists("/lib/sub").unwrap());
+
+    let lua = mluau::Lua::new();
+    let c = AssetRequirer::new(fs.clone(), "tree".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let result: String = lua.load(main).set_name("/main").call(()).unwrap();
+    assert_eq!(result, "b");
+
+    assert_eq!(
+        MemoryTreeBuilder::new()
+            .with_file("a.luau", "")
+            .with_file("/a.luau", "")
+            .build()
+            .unwrap_err(),
+        MemoryTreeError::DuplicateFile("a.luau".to_string())
+    );
+    assert_eq!(
+        MemoryTreeBuilder::new()
+            .with_file("a", "")
+            .with_file("a/b.luau", "")
+            .build()
+            .unwrap_err(),
+        MemoryTreeError::FileDirectoryConflict {
+            file: "a".to_string(),
+            path: "a/b.luau".to_string()
+        }
+    );
+    assert_eq!(
+        MemoryTreeBuilder::new()
+            .with_file("a/b.luau", "")
+            .with_file("a", "")
+            .build()
+            .unwrap_err(),
+        MemoryTreeError::FileDirectoryConflict {
+            file: "a".to_string(),
+            path: "a/b.luau".to_string()
+        }
+    );
+    assert!(matches!(
+        MemoryTreeBuilder::new()
+            .with_file("../escape.luau", "")
+            .build(),
+        Err(MemoryTreeError::InvalidPath { .. })
+    ));
+    assert!(matches!(
+        MemoryTreeBuilder::new().with_dir("/./").build(),
+        Err(MemoryTreeError::InvalidPath { .. })
+    ));
+}
+
+#[test]
+fn test_snapshot_from_dir() {
+    use crate::SnapshotFilter;
+
+    let root = std::env::temp_dir().join(format!("mluau-require-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    std::fs::create_dir_all(root.join("node_modules")).unwrap();
+    std::fs::write(root.join("main.luau"), "return require('./lib/a')").unwrap();
+    std::fs::write(root.join("lib/a.luau"), "return 'a'").unwrap();
+    std::fs::write(root.join("lib/notes.md"), "notes").unwrap();
+    std::fs::write(root.join("node_modules/x.luau"), "return 'x'").unwrap();
+
+    let filter = SnapshotFilter::new()
+        .with_include("**/*.luau")
+        .with_exclude("node_modules");
+    let fs = FilesystemWrapper::snapshot_from_dir(&root, &filter);
+
+    // Later changes on disk are not visible to the snapshot
+    std::fs::write(root.join("lib/a.luau"), "return 'changed'").unwrap();
+    let too_large =
+        FilesystemWrapper::snapshot_from_dir(&root, &SnapshotFilter::new().with_max_file_size(10));
+    let too_large_total =
+        FilesystemWrapper::snapshot_from_dir(&root, &SnapshotFilter::new().with_max_total_size(30));
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let fs = fs.expect("Failed to snapshot directory");
+    assert!(fs.is_file("/lib/a.luau".to_string()).unwrap());
+    assert!(!fs.exists("/lib/notes.md").unwrap());
+    assert!(!fs.exists("/node_modules").unwrap());
+
+    let lua = mluau::Lua::new();
+    let c = AssetRequirer::new(fs.clone(), "snapshot".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let result: String = lua.load(main).set_name("/main").call(()).unwrap();
+    assert_eq!(result, "a");
+
+    assert!(too_large.is_err());
+    assert!(too_large_total.is_err());
+}
+
+#[test]
+fn test_read_only_fs() {
+    let mut tree = std::collections::HashMap::new();
+    tree.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "./lib".to_string(),
+        }),
+    );
+    tree.insert(
+        "main.luau".to_string(),
+        "return require('@lib/a')".to_string(),
+    );
+    tree.insert("lib/a.luau".to_string(), "return 'a'".to_string());
+
+    let mem_fs = super::memoryvfs::create_memory_vfs_from_map(&tree).expect("Failed to make vfs");
+    let fs = FilesystemWrapper::read_only(mem_fs);
+
+    assert!(fs.create_file("/new.luau").is_err());
+    assert!(fs.create_dir("/new").is_err());
+    assert!(fs.append_file("/main.luau").is_err());
+    assert!(fs.remove_file("/main.luau").is_err());
+    assert!(fs.remove_dir("/lib").is_err());
+    assert!(fs.is_file("/main.luau".to_string()).unwrap());
+
+    let lua = mluau::Lua::new();
+    let c = AssetRequirer::new(fs.clone(), "readonly".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let result: String = lua.load(main).set_name("/main").call(()).unwrap();
+    assert_eq!(result, "a");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_root_jail() {
+    use crate::{ResolveError, RootEscapeError};
+    use mluau::prelude::{LuaNavigateError, LuaRequire};
+
+    let base = std::env::temp_dir().join(format!("mluau-require-jail-{}", std::process::id()));
+    let root = base.join("root");
+    let outside = base.join("outside");
+    std::fs::create_dir_all(root.join("lib")).unwrap();
+    std::fs::create_dir_all(&outside).unwrap();
+    std::fs::write(root.join("lib/a.luau"), "return 'a'").unwrap();
+    std::fs::write(outside.join("secret.luau"), "return 'secret'").unwrap();
+    std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+    std::fs::write(
+        root.join(".luaurc"),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "outside".to_string() => "x/../../outside".to_string(),
+            "lib".to_string() => "/lib".to_string(),
+        }),
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("main.luau"),
+        r#"
+assert(require("./lib/a") == "a")
+assert(require("@lib/a") == "a")
+local ok, err = pcall(require, "@tpyo/a")
+assert(not ok and not string.find(tostring(err), "escapes", 1, true), tostring(err))
+assert(not pcall(require, "./escape/secret"))
+assert(not pcall(require, "@outside/secret"))
+assert(not pcall(require, "../outside/secret"))
+return true
+"#,
+    )
+    .unwrap();
+
+    let lua = mluau::Lua::new();
+    let fs = FilesystemWrapper::new(vfs::PhysicalFS::new(&root));
+    let c = AssetRequirer::new(fs.clone(), "jail".to_string(), lua.globals()).with_root_jail(&root);
+    let mut resolver = c.clone();
+
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+    let main = fs.get_file("/main.luau".to_string()).unwrap();
+    let result = lua.load(main).set_name("/main").call::<bool>(());
+
+    let unknown = resolver.resolve("/main", "@tpyo/a");
+    let resolved = [
+        "./lib/a",
+        "@lib/a",
+        "./escape/secret",
+        "@outside/secret",
+        "../outside/secret",
+    ]
+    .map(|path| resolver.resolve("/main", path));
+
+    // Climbing above the root is an escape rather than a missing module
+    let mut navigator = resolver.clone();
+    assert!(navigator.reset("/main").is_ok());
+    assert!(navigator.to_parent().is_ok());
+    let escapes = [
+        navigator.to_parent(),
+        navigator.jump_to_alias("x/../../outside"),
+        navigator.reset("../outside/secret"),
+    ];
+    std::fs::remove_dir_all(&base).unwrap();
+
+    assert!(result.expect("Failed to run main"));
+    assert_eq!(unknown, Err(ResolveError::UnknownAlias("tpyo".to_string())));
+    assert_eq!(resolved[0], Ok("/lib/a.luau".to_string()));
+    assert_eq!(resolved[1], Ok("/lib/a.luau".to_string()));
+    for escaped in &resolved[2..] {
+        assert!(
+            matches!(escaped, Err(ResolveError::EscapesRoot(_))),
+            "{escaped:?}"
+        );
+    }
+    for (i, escaped) in escapes.iter().enumerate() {
+        assert!(
+            matches!(
+                escaped,
+                Err(LuaNavigateError::Other(e)) if e.downcast_ref::<RootEscapeError>().is_some()
+            ),
+            "escape {i} was not rejected"
+        );
+    }
+}
+
+#[test]
+fn test_resource_limits() {
+    use crate::ResourceLimits;
+    use mluau::prelude::LuaRequire;
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("main.luau".to_string(), "return 0".to_string());
+    tree.insert("a.luau".to_string(), "return require('./b')".to_string());
+    tree.insert("b.luau".to_string(), "return require('./c')".to_string());
+    tree.insert("c.luau".to_string(), "return 'c'".to_string());
+    tree.insert("broken.luau".to_string(), "return (".to_string());
+    tree.insert(
+        "big.luau".to_string(),
+        format!("return '{}'", "x".repeat(100)),
+    );
+    tree.insert(
+        "cfg/.luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "long-alias-name".to_string() => "../lib".to_string(),
+        }),
+    );
+    tree.insert(
+        "cfg/main.luau".to_string(),
+        "return require('@long-alias-name/x')".to_string(),
+    );
+    tree.insert("lib/x.luau".to_string(), "return 'x'".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let run = |limits: ResourceLimits, chunk_name: &str, code: &str| {
+        let lua = mluau::Lua::new();
+        let c = AssetRequirer::new(fs.clone(), "limits".to_string(), lua.globals())
+            .with_resource_limits(limits);
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        lua.load(code).set_name(chunk_name).call::<String>(())
+    };
+
+    let unlimited = ResourceLimits::default();
+    assert_eq!(
+        run(unlimited, "/main", "return require('./a')").unwrap(),
+        "c"
+    );
+
+    let size = ResourceLimits {
+        max_module_size: Some(50),
+        ..Default::default()
+    };
+    assert!(run(size, "/main", "return require('./a')").is_ok());
+    assert!(run(size, "/main", "return require('./big')").is_err());
+
+    // Content hashing reads the whole module, so oversized modules are keyed by path instead
+    let lua = mluau::Lua::new();
+    let mut hashed = AssetRequirer::new(fs.clone(), "limits".to_string(), lua.globals())
+        .with_resource_limits(size)
+        .with_cache_key_strategy(crate::CacheKeyStrategy::ContentHash);
+    assert!(hashed.reset("/main").is_ok());
+    assert!(hashed.to_parent().is_ok());
+    assert!(hashed.to_child("big").is_ok());
+    assert_eq!(hashed.cache_key(), "limits@/big.luau");
+    assert!(hashed.to_parent().is_ok());
+    assert!(hashed.to_child("a").is_ok());
+    assert!(hashed.cache_key().starts_with("limits#sha256:"));
+
+    let depth = ResourceLimits {
+        max_require_depth: Some(2),
+        ..Default::default()
+    };
+    assert!(run(depth, "/main", "return require('./b')").is_ok());
+    assert!(run(depth, "/main", "return require('./a')").is_err());
+
+    let count = ResourceLimits {
+        max_modules: Some(2),
+        ..Default::default()
+    };
+    assert!(run(count, "/main", "return require('./b')").is_ok());
+    assert!(run(count, "/main", "require('./c') return require('./a')").is_err());
+    // Modules that fail to compile do not use up the budget
+    let broken = "pcall(require, './broken') return require('./b')";
+    assert!(run(count, "/main", broken).is_ok());
+
+    let config = ResourceLimits {
+        max_config_size: Some(16),
+        ..Default::default()
+    };
+    assert!(run(config, "/main", "return require('./a')").is_ok());
+    assert!(run(config, "/cfg/main", "return require('@long-alias-name/x')").is_err());
+    assert_eq!(
+        run(
+            unlimited,
+            "/cfg/main",
+            "return require('@long-alias-name/x')"
+        )
+        .unwrap(),
+        "x"
+    );
+}
+
+#[test]
+fn test_lockfile() {
+    use crate::{IntegrityError, Lockfile};
+    use std::collections::HashMap;
+
+    let mut tree = HashMap::new();
+    tree.insert("main.luau".to_string(), "return 0".to_string());
+    tree.insert("a.luau".to_string(), "return require('./b')".to_string());
+    tree.insert("b.luau".to_string(), "return 'b'".to_string());
+    tree.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "./lib".to_string(),
+        }),
+    );
+    tree.insert("lib/x.luau".to_string(), "return 'x'".to_string());
+
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let lockfile = Lockfile::generate(&fs).unwrap();
+    assert_eq!(lockfile.files.len(), 5);
+    assert!(lockfile.files.contains_key("/lib/x.luau"));
+    assert_eq!(Lockfile::from_json(&lockfile.to_json()).unwrap(), lockfile);
+    assert!(Lockfile::from_json(br#"{"version": 2, "files": {}}"#).is_err());
+
+    assert!(lockfile.verify("/b.luau", b"return 'b'").is_ok());
+    assert!(matches!(
+        lockfile.verify("/b.luau", b"return 'evil'"),
+        Err(IntegrityError::Mismatch { .. })
+    ));
+    assert_eq!(
+        lockfile.verify("/c.luau", b""),
+        Err(IntegrityError::Unlisted {
+            path: "/c.luau".to_string()
+        })
+    );
+
+    let run = |tree: &HashMap<String, String>, verify_configs: bool, code: &str| {
+        let fs = super::memoryvfs::create_vfs_from_map(tree).expect("Failed to make vfs");
+        let lua = mluau::Lua::new();
+        let c = AssetRequirer::new(fs, "lockfile".to_string(), lua.globals())
+            .with_lockfile(lockfile.clone(), verify_configs);
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        lua.load(code).set_name("/main").call::<String>(())
+    };
+
+    assert_eq!(run(&tree, true, "return require('./a')").unwrap(), "b");
+    assert_eq!(run(&tree, true, "return require('@lib/x')").unwrap(), "x");
+
+    let mut tampered = tree.clone();
+    tampered.insert("b.luau".to_string(), "return 'evil'".to_string());
+    assert!(run(&tampered, false, "return require('./a')").is_err());
+
+    let mut unexpected = tree.clone();
+    unexpected.insert("c.luau".to_string(), "return 'c'".to_string());
+    assert!(run(&unexpected, false, "return require('./c')").is_err());
+
+    let mut tampered_config = tree.clone();
+    tampered_config.insert(
+        ".luaurc".to_string(),
+        create_luaurc_with_aliases(indexmap::indexmap! {
+            "lib".to_string() => "./lib".to_string(),
+            "other".to_string() => "./lib".to_string(),
+        }),
+    );
+    assert_eq!(
+        run(&tampered_config, false, "return require('@lib/x')").unwrap(),
+        "x"
+    );
+    assert!(run(&tampered_config, true, "return require('@lib/x')").is_err());
+}
+
+#[cfg(feature = "signed-manifest")]
+#[test]
+fn test_signed_manifest_fs() {
+    use crate::{Lockfile, SignedManifestFS};
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::collections::HashMap;
+
+    let mut tree = HashMap::new();
+    tree.insert("a.luau".to_string(), "return require('./b')".to_string());
+    tree.insert("b.luau".to_string(), "return 'b'".to_string());
+    tree.insert("lib/x.luau".to_string(), "return 'x'".to_string());
+
+    let trusted = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let manifest = Lockfile::generate(&trusted).unwrap().to_json();
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let signature = key.sign(&manifest).to_bytes();
+    let public_key = key.verifying_key().to_bytes();
+
+    let signed = |tree: &HashMap<String, String>| {
+        let mem_fs =
+            super::memoryvfs::create_memory_vfs_from_map(tree).expect("Failed to make vfs");
+        FilesystemWrapper::signed(mem_fs, &manifest, &signature, &public_key)
+    };
+    let run = |fs: FilesystemWrapper, code: &str| {
+        let lua = mluau::Lua::new();
+        let c = AssetRequirer::new(fs, "signed".to_string(), lua.globals());
+        lua.globals()
+            .set("require", lua.create_require_function(c).unwrap())
+            .unwrap();
+        lua.load(code).set_name("/main").call::<String>(())
+    };
+
+    let mem_fs = super::memoryvfs::create_memory_vfs_from_map(&tree).expect("Failed to make vfs");
+    let other_key = SigningKey::from_bytes(&[8; 32]).verifying_key().to_bytes();
+    assert!(SignedManifestFS::new(mem_fs, &manifest, &signature, &other_key).is_err());
+    let mut forged = manifest.clone();
+    forged.push(b'\n');
+    let mem_fs = super::memoryvfs::create_memory_vfs_from_map(&tree).expect("Failed to make vfs");
+    assert!(SignedManifestFS::new(mem_fs, &forged, &signature, &public_key).is_err());
+
+    let mut unsigned = tree.clone();
+    unsigned.insert("c.luau".to_string(), "return 'c'".to_string());
+    unsigned.insert("extra/y.luau".to_string(), "return 'y'".to_string());
+    let fs = signed(&unsigned).unwrap();
+    assert!(fs.is_file("/a.luau".to_string()).unwrap());
+    assert!(!fs.is_file("/c.luau".to_string()).unwrap());
+    assert!(!fs.is_dir("/extra".to_string()).unwrap());
+    let mut entries = fs.read_dir("").unwrap().collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, vec!["a.luau", "b.luau", "lib"]);
+    assert!(fs.create_file("/c.luau").is_err());
+
+    assert_eq!(run(fs.clone(), "return require('./a')").unwrap(), "b");
+    assert_eq!(run(fs.clone(), "return require('./lib/x')").unwrap(), "x");
+    assert!(run(fs.clone(), "return require('./c')").is_err());
+    assert!(run(fs, "return require('./extra/y')").is_err());
+
+    // Listed files are verified up front
+    let mut tampered = tree.clone();
+    tampered.insert("b.luau".to_string(), "return 'evil'".to_string());
+    assert!(signed(&tampered).is_err());
+    let mut missing = tree.clone();
+    missing.remove("lib/x.luau");
+    assert!(signed(&missing).is_err());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_spans() {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata};
+
+    /// Records span names, their `status` fields and event messages
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<&'static str>>>,
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct FieldVisitor<'a> {
+        prefix: &'a str,
+        lines: &'a mut Vec<String>,
+    }
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if matches!(field.name(), "status" | "message") {
+                self.lines.push(format!("{}{value}", self.prefix));
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.record_str(field, &format!("{value:?}"));
+        }
+    }
+
+    impl tracing::Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span.metadata().name());
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let name = self.spans.lock().unwrap()[span.into_u64() as usize - 1];
+            values.record(&mut FieldVisitor {
+                prefix: &format!("{name}: "),
+                lines: &mut self.lines.lock().unwrap(),
+            });
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut FieldVisitor {
+                prefix: "",
+                lines: &mut self.lines.lock().unwrap(),
+            });
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    let mut tree = std::collections::HashMap::new();
+    tree.insert("main.luau".to_string(), "return 0".to_string());
+    tree.insert("ok.luau".to_string(), "return 1".to_string());
+
+    let lua = mluau::Lua::new();
+    let fs = super::memoryvfs::create_vfs_from_map(&tree).expect("Failed to make vfs");
+    let c = AssetRequirer::new(fs, "tracing".to_string(), lua.globals());
+    lua.globals()
+        .set("require", lua.create_require_function(c).unwrap())
+        .unwrap();
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        for path in ["./ok", "./missing"] {
+            let _ = lua
+                .load(format!("return require('{path}')"))
+                .set_name("/main")
+                .call::<mluau::Value>(());
+        }
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    for name in ["reset", "to_child", "loader"] {
+        assert!(spans.contains(&name), "missing span {name} in {spans:?}");
+    }
+
+    let lines = recorder.lines.lock().unwrap();
+    for line in [
+        "reset: success",
+        "to_child: success",
+        "to_child: not_found",
+        "loader: success",
+        "is_file",
+        "reset_to_path",
+    ] {
+        assert!(
+            lines.iter().any(|l| l == line),
+            "missing {line:?} in {lines:?}"
+        );
+    }
+}
@@ -2,6 +2,9 @@ use std::collections::VecDeque;
 use std::path::{Component, Path, PathBuf};
 
 // From https://github.com/luau-lang/luau/blob/master/CLI/src/FileUtils.cpp#L49
+//
+// NOTE: outside Windows this is true for paths that do *not* start with '/', which the
+// navigator's path handling is built around. Use `starts_with('/')` to check VFS paths.
 pub(super) fn is_absolute_path(path: &str) -> bool {
     #[cfg(windows)]
     {
@@ -1,7 +1,10 @@
 // Ported from https://github.com/luau-lang/luau/blob/master/CLI/src/VfsNavigator.cpp
+use super::events::{EventHandler, RequireEvent};
 use super::fswrapper::FilesystemWrapper;
 use super::utils::{is_absolute_path, normalize_path};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use vfs::VfsResult;
 
 const SUFFIXES: [&str; 2] = [".luau", ".lua"];
 const INIT_SUFFIXES: [&str; 2] = ["/init.luau", "/init.lua"];
@@ -13,11 +16,26 @@ pub enum NavigationStatus {
     Ambiguous,
 }
 
+/// How module paths are matched against the filesystem
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    /// Paths must match exactly
+    #[default]
+    Sensitive,
+    /// Paths differing only in case from an existing file or directory resolve to it
+    Insensitive,
+    /// Paths must match exactly, but differently-cased matches are reported
+    WarnOnMismatch,
+}
+
+#[derive(Clone)]
 pub struct ResolvedRealPath {
     status: NavigationStatus,
     real_path: Option<String>,
+    probes: Vec<String>,
 }
 
+#[derive(Clone)]
 pub struct VfsNavigator {
     pub(crate) fs: FilesystemWrapper,
     real_path: String,
@@ -25,6 +43,13 @@ pub struct VfsNavigator {
     absolute_path_prefix: String,
     module_path: String,
     absolute_module_path: String,
+    probes: Vec<String>,
+    asset_extensions: Vec<String>,
+    probe_asset_extensions: bool,
+    mounts: Vec<(String, FilesystemWrapper)>,
+    case_sensitivity: CaseSensitivity,
+    case_mismatch_handler: Option<Rc<dyn Fn(&str, &str)>>,
+    event_handler: Option<EventHandler>,
 }
 
 impl VfsNavigator {
@@ -36,8 +61,101 @@ impl VfsNavigator {
             absolute_path_prefix: "".to_string(),
             module_path: "/".to_string(),
             absolute_module_path: "/".to_string(),
+            probes: Vec::new(),
+            asset_extensions: Vec::new(),
+            probe_asset_extensions: false,
+            mounts: Vec::new(),
+            case_sensitivity: CaseSensitivity::default(),
+            case_mismatch_handler: None,
+            event_handler: None,
+        }
+    }
+
+    pub(crate) fn set_event_handler(&mut self, handler: EventHandler) {
+        self.event_handler = Some(handler);
+    }
+
+    pub(crate) fn set_case_sensitivity(&mut self, case_sensitivity: CaseSensitivity) {
+        self.case_sensitivity = case_sensitivity;
+    }
+
+    pub(crate) fn set_case_mismatch_handler(&mut self, handler: Rc<dyn Fn(&str, &str)>) {
+        self.case_mismatch_handler = Some(handler);
+    }
+
+    pub(crate) fn clear_event_handler(&mut self) {
+        self.event_handler = None;
+    }
+
+    /// Mounts a separate filesystem so its root is visible at `prefix` (e.g. `/@std`)
+    pub(crate) fn add_mount(&mut self, prefix: String, fs: FilesystemWrapper) {
+        self.mounts.retain(|(p, _)| *p != prefix);
+        self.mounts.push((prefix, fs));
+    }
+
+    /// Returns the filesystem backing `path` along with the path inside that filesystem
+    pub(crate) fn fs_for(&self, path: String) -> (&FilesystemWrapper, String) {
+        let path = FilesystemWrapper::path_fix(path);
+        for (prefix, fs) in self.mounts.iter() {
+            if path == *prefix {
+                return (fs, "/".to_string());
+            }
+
+            if let Some(rest) = path.strip_prefix(prefix.as_str())
+                && rest.starts_with('/')
+            {
+                return (fs, rest.to_string());
+            }
+        }
+
+        (&self.fs, path)
+    }
+
+    /// Whether `path` lies in a mounted filesystem rather than the main one
+    pub(crate) fn is_mounted(&self, path: String) -> bool {
+        let (fs, _) = self.fs_for(path);
+        !Rc::ptr_eq(&fs.0, &self.fs.0)
+    }
+
+    pub fn is_file(&self, path: String) -> VfsResult<bool> {
+        let (fs, path) = self.fs_for(path);
+        fs.is_file(path)
+    }
+
+    pub fn is_dir(&self, path: String) -> VfsResult<bool> {
+        let (fs, path) = self.fs_for(path);
+        fs.is_dir(path)
+    }
+
+    /// Returns the size of the file at `path` without reading it
+    pub fn file_size(&self, path: String) -> VfsResult<u64> {
+        let (fs, path) = self.fs_for(path);
+        Ok(fs.metadata(&path)?.len)
+    }
+
+    pub fn get_file(&self, path: String) -> VfsResult<Vec<u8>> {
+        let (fs, path) = self.fs_for(path);
+        fs.get_file(path)
+    }
+
+    pub fn read_dir(&self, path: String) -> VfsResult<Vec<String>> {
+        let (fs, path) = self.fs_for(path);
+        // rust-vfs refers to the root directory as ""
+        let path = if path == "/" { "" } else { path.as_str() };
+        Ok(fs.read_dir(path)?.collect())
+    }
+
+    /// Registers a non-Luau extension (without the leading dot) that can be required directly
+    pub(crate) fn add_asset_extension(&mut self, extension: &str) {
+        if !self.asset_extensions.iter().any(|e| e == extension) {
+            self.asset_extensions.push(extension.to_string());
         }
     }
+
+    /// Sets whether extensionless requires also probe registered asset extensions
+    pub(crate) fn set_probe_asset_extensions(&mut self, probe: bool) {
+        self.probe_asset_extensions = probe;
+    }
 }
 
 impl VfsNavigator {
@@ -45,8 +163,8 @@ impl VfsNavigator {
         &self,
         module_path: String,
     ) -> Result<ResolvedRealPath, crate::Error> {
-        let mut found = false;
-        let mut suffix = "";
+        let mut found: Option<String> = None;
+        let mut probes = Vec::new();
 
         // Get the position of the last slash
         let last_slash = module_path.rfind('/').unwrap_or(0);
@@ -56,68 +174,171 @@ impl VfsNavigator {
             ""
         };
 
-        #[cfg(feature = "log")]
-        log::trace!("Get_real_path: {module_path}");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%module_path, "get_real_path");
 
+        let mut candidates = Vec::new();
         if last_component != "init" {
             for potential_suffix in SUFFIXES.iter() {
-                if self
-                    .fs
-                    .is_file(format!("{module_path}{potential_suffix}"))?
-                {
-                    if found {
-                        return Ok(ResolvedRealPath {
-                            status: NavigationStatus::Ambiguous,
-                            real_path: None,
-                        });
-                    }
+                candidates.push(format!("{module_path}{potential_suffix}"));
+            }
+        }
+
+        // Assets are required with their extension, e.g. `./config.json`
+        if self
+            .asset_extensions
+            .iter()
+            .any(|ext| last_component.ends_with(&format!(".{ext}")))
+        {
+            candidates.push(module_path.clone());
+        }
+
+        if self.probe_asset_extensions && last_component != "init" {
+            for ext in self.asset_extensions.iter() {
+                candidates.push(format!("{module_path}.{ext}"));
+            }
+        }
 
-                    suffix = potential_suffix;
-                    found = true;
+        for candidate in candidates {
+            probes.push(candidate.clone());
+            if let Some(real) = self.probe(candidate, true)? {
+                if found.is_some() {
+                    return Ok(ResolvedRealPath {
+                        status: NavigationStatus::Ambiguous,
+                        real_path: None,
+                        probes,
+                    });
                 }
+
+                found = Some(real);
             }
         }
 
-        if self.fs.is_dir(module_path.clone())? {
-            if found {
+        if probes.last() != Some(&module_path) {
+            probes.push(module_path.clone());
+        }
+        if let Some(directory) = self.probe(module_path.clone(), false)? {
+            if found.is_some() {
                 return Ok(ResolvedRealPath {
                     status: NavigationStatus::Ambiguous,
                     real_path: None,
+                    probes,
                 });
             }
 
             for potential_suffix in INIT_SUFFIXES.iter() {
-                if self
-                    .fs
-                    .is_file(format!("{module_path}{potential_suffix}"))?
-                {
-                    if found {
+                let candidate = format!("{directory}{potential_suffix}");
+                probes.push(candidate.clone());
+                if let Some(real) = self.probe(candidate, true)? {
+                    if found.is_some() {
                         return Ok(ResolvedRealPath {
                             status: NavigationStatus::Ambiguous,
                             real_path: None,
+                            probes,
                         });
                     }
 
-                    suffix = potential_suffix;
-                    found = true;
+                    found = Some(real);
                 }
             }
 
-            found = true;
+            if found.is_none() {
+                found = Some(directory);
+            }
         }
 
-        if !found {
+        let Some(real_path) = found else {
             return Ok(ResolvedRealPath {
                 status: NavigationStatus::NotFound,
                 real_path: None,
+                probes,
             });
-        }
+        };
 
         Ok(ResolvedRealPath {
             status: NavigationStatus::Success,
-            real_path: Some(format!("{module_path}{suffix}")),
+            real_path: Some(real_path),
+            probes,
         })
     }
+
+    /// Checks whether `candidate` is a file (or directory), returning the path it was found at
+    ///
+    /// With [`CaseSensitivity::Insensitive`] this may be a differently-cased path.
+    fn probe(&self, candidate: String, file: bool) -> VfsResult<Option<String>> {
+        let exists = |path: String| {
+            if file {
+                self.is_file(path)
+            } else {
+                self.is_dir(path)
+            }
+        };
+
+        if exists(candidate.clone())? {
+            return Ok(Some(candidate));
+        }
+
+        if self.case_sensitivity == CaseSensitivity::Sensitive {
+            return Ok(None);
+        }
+
+        let Some(actual) = self.find_case_insensitive(&candidate) else {
+            return Ok(None);
+        };
+        if !exists(actual.clone())? {
+            return Ok(None);
+        }
+
+        match self.case_sensitivity {
+            CaseSensitivity::Insensitive => Ok(Some(actual)),
+            _ => {
+                self.report_case_mismatch(&candidate, &actual);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Finds the actual path matching `path` when compared case-insensitively component by
+    /// component. Returns `None` if a component has no match or several differently-cased ones
+    fn find_case_insensitive(&self, path: &str) -> Option<String> {
+        let mut actual = String::new();
+        for component in FilesystemWrapper::path_fix(path.to_string())
+            .split('/')
+            .filter(|c| !c.is_empty())
+        {
+            let exact = format!("{actual}/{component}");
+            if self.is_file(exact.clone()).unwrap_or(false)
+                || self.is_dir(exact.clone()).unwrap_or(false)
+            {
+                actual = exact;
+                continue;
+            }
+
+            let lowercase = component.to_lowercase();
+            let mut matches = self
+                .read_dir(actual.clone())
+                .ok()?
+                .into_iter()
+                .filter(|entry| entry.to_lowercase() == lowercase);
+            let name = matches.next()?;
+            if matches.next().is_some() {
+                return None;
+            }
+
+            actual = format!("{actual}/{name}");
+        }
+
+        Some(actual)
+    }
+
+    fn report_case_mismatch(&self, requested: &str, actual: &str) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(requested, actual, "case mismatch");
+
+        if let Some(handler) = self.case_mismatch_handler.as_ref() {
+            handler(requested, actual);
+        }
+    }
 }
 
 fn get_module_path(file_path: &mut String) -> String {
@@ -129,8 +350,8 @@ fn get_module_path(file_path: &mut String) -> String {
     // Create a string view (slice) from the modified path
     let mut path_view: &str = file_path;
 
-    #[cfg(feature = "log")]
-    log::trace!("path_view: {path_view}");
+    #[cfg(feature = "tracing")]
+    tracing::trace!(path_view, "get_module_path");
 
     // Handle absolute paths
     if is_absolute_path(path_view) {
@@ -185,14 +406,27 @@ fn get_module_path(file_path: &mut String) -> String {
 impl VfsNavigator {
     pub fn update_real_paths(&mut self) -> Result<NavigationStatus, crate::Error> {
         let result = self.get_real_path(self.module_path.clone())?;
-        let absolute_result = self.get_real_path(self.absolute_module_path.clone())?;
+        let absolute_result = if self.absolute_module_path == self.module_path {
+            result.clone()
+        } else {
+            self.get_real_path(self.absolute_module_path.clone())?
+        };
+        self.probes = absolute_result.probes;
         if result.status != NavigationStatus::Success
             || absolute_result.status != NavigationStatus::Success
         {
             if self.module_path.is_empty() {
                 // DEVIATION: Support rooted init.luau
-                #[cfg(feature = "log")]
-                log::trace!("Deviation triggered: empty module_path");
+                #[cfg(feature = "tracing")]
+                tracing::trace!("Deviation triggered: empty module_path");
+            }
+            if let Some(handler) = self.event_handler.as_ref() {
+                handler(&RequireEvent::ResolveFailed {
+                    module_path: self.absolute_module_path.clone(),
+                    ambiguous: result.status == NavigationStatus::Ambiguous
+                        || absolute_result.status == NavigationStatus::Ambiguous,
+                    probes: self.probes.clone(),
+                });
             }
             return Ok(result.status);
         }
@@ -226,8 +460,8 @@ impl VfsNavigator {
     pub fn reset_to_path(&mut self, path: &Path) -> Result<NavigationStatus, crate::Error> {
         let mut normalized_path = normalize_path(path).to_string_lossy().to_string();
 
-        #[cfg(feature = "log")]
-        log::trace!("Normalized path: {normalized_path}");
+        #[cfg(feature = "tracing")]
+        tracing::trace!(%normalized_path, "reset_to_path");
 
         if is_absolute_path(&normalized_path) {
             self.module_path = get_module_path(&mut normalized_path);
@@ -259,15 +493,15 @@ impl VfsNavigator {
             self.absolute_module_path = "/".to_string(); // DEVIATION: Support rooted modules
         }
 
-        #[cfg(feature = "log")]
-        log::trace!("module_path: {}", self.module_path);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(module_path = %self.module_path, "reset_to_path");
         self.update_real_paths()
     }
 
     #[allow(clippy::wrong_self_convention)]
     pub fn to_parent(&mut self) -> Result<NavigationStatus, crate::Error> {
-        #[cfg(feature = "log")]
-        log::trace!("AbsModPath: {}", self.absolute_module_path);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(absolute_module_path = %self.absolute_module_path, "to_parent");
 
         if self.absolute_module_path.is_empty() {
             return Ok(NavigationStatus::NotFound);
@@ -277,8 +511,8 @@ impl VfsNavigator {
         if self.absolute_module_path == "/" {
             self.module_path = "".to_string();
             self.absolute_module_path = "".to_string();
-            #[cfg(feature = "log")]
-            log::trace!("Deviation: set module_path + abs_module_path to empty");
+            #[cfg(feature = "tracing")]
+            tracing::trace!("Deviation: set module_path + abs_module_path to empty");
             return self.update_real_paths();
         }
 
@@ -302,14 +536,16 @@ impl VfsNavigator {
         self.module_path = normalize_path(&PathBuf::from(self.module_path.clone() + "/.."))
             .to_string_lossy()
             .to_string();
-        #[cfg(feature = "log")]
-        log::trace!("NewModPath: {}", self.module_path);
         self.absolute_module_path =
             normalize_path(&PathBuf::from(self.absolute_module_path.clone() + "/.."))
                 .to_string_lossy()
                 .to_string();
-        #[cfg(feature = "log")]
-        log::trace!("NewAbsModPath: {}", self.absolute_module_path);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            module_path = %self.module_path,
+            absolute_module_path = %self.absolute_module_path,
+            "moved to parent"
+        );
         self.update_real_paths()
     }
 
@@ -335,23 +571,43 @@ impl VfsNavigator {
         &self.absolute_real_path
     }
 
+    pub fn get_module_path(&self) -> &str {
+        &self.module_path
+    }
+
+    pub fn get_absolute_module_path(&self) -> &str {
+        &self.absolute_module_path
+    }
+
+    /// Returns the candidate paths checked by the last navigation step
+    pub fn get_probes(&self) -> &[String] {
+        &self.probes
+    }
+
     pub fn get_luaurc_path(&self) -> String {
-        #[cfg(feature = "log")]
-        log::trace!("get_luaurc_path called");
-        let mut directory = self.real_path.as_str();
+        #[cfg(feature = "tracing")]
+        tracing::trace!("get_luaurc_path called");
+        format!("{}/.luaurc", self.get_config_directory())
+    }
+
+    pub fn get_config_luau_path(&self) -> String {
+        format!("{}/.config.luau", self.get_config_directory())
+    }
+
+    /// Returns the directory searched for config files at the current position
+    fn get_config_directory(&self) -> &str {
+        let directory = self.real_path.as_str();
 
         for suffix in INIT_SUFFIXES.iter() {
             if directory.ends_with(suffix) {
-                directory = &directory[..directory.len() - suffix.len()];
-                return format!("{directory}/.luaurc");
+                return &directory[..directory.len() - suffix.len()];
             }
         }
         for suffix in SUFFIXES.iter() {
             if directory.ends_with(suffix) {
-                directory = &directory[..directory.len() - suffix.len()];
-                return format!("{directory}/.luaurc");
+                return &directory[..directory.len() - suffix.len()];
             }
         }
-        format!("{directory}/.luaurc")
+        directory
     }
 }